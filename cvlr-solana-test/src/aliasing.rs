@@ -0,0 +1,152 @@
+//! Account lists in which the same account is passed more than once
+//!
+//! The runtime passes a duplicate account as another `AccountInfo` over the
+//! same lamports and data, so a write through one is visible through the
+//! other. Distinct nondet accounts never alias, even when their keys are
+//! equal, so aliasing has to be requested explicitly.
+
+use crate::instruction::InstructionHarness;
+use crate::lamports::check_lamports_conserved;
+use cvlr::mathint::NativeInt as MathInt;
+use cvlr::prelude::*;
+use cvlr_solana::{clog_acc_info, cvlr_deserialize_nondet_accounts};
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError,
+    program_pack::Pack, pubkey::Pubkey,
+};
+use spl_token::{
+    processor::Processor,
+    state::{Account, Mint, Multisig},
+};
+
+/// [accounts] with position [dst] replaced by the account at [src]
+pub fn alias<'a>(accounts: &[AccountInfo<'a>], dst: usize, src: usize) -> Vec<AccountInfo<'a>> {
+    let mut aliased = accounts.to_vec();
+    aliased[dst] = accounts[src].clone();
+    aliased
+}
+
+/// [accounts] where every position either keeps its account or duplicates an
+/// earlier position
+pub fn nondet_aliased_accounts<'a>(accounts: &[AccountInfo<'a>]) -> Vec<AccountInfo<'a>> {
+    let mut aliased: Vec<AccountInfo<'a>> = Vec::with_capacity(accounts.len());
+    for (i, info) in accounts.iter().enumerate() {
+        let j: usize = nondet();
+        let info = if j < i {
+            aliased[j].clone()
+        } else {
+            info.clone()
+        };
+        aliased.push(info);
+    }
+    aliased
+}
+
+fn amount_from_data(data: &[u8]) -> Result<u64, ProgramError> {
+    data.get(..8)
+        .and_then(|bytes| bytes.try_into().ok())
+        .map(u64::from_le_bytes)
+        .ok_or(ProgramError::InvalidInstructionData)
+}
+
+/// Moves lamports between the first two accounts, reading both balances before
+/// writing either of them
+///
+/// When the two accounts alias, the second write wins and lamports are minted.
+fn process_transfer_naive(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let [from, to, ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    let amount = amount_from_data(data)?;
+    let from_lamports = from
+        .lamports()
+        .checked_sub(amount)
+        .ok_or(ProgramError::InsufficientFunds)?;
+    let to_lamports = to
+        .lamports()
+        .checked_add(amount)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    **from.try_borrow_mut_lamports()? = from_lamports;
+    **to.try_borrow_mut_lamports()? = to_lamports;
+    Ok(())
+}
+
+/// Like [process_transfer_naive], but rejects a transfer to the same account
+fn process_transfer_checked(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let [from, to, ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    if from.key == to.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+    process_transfer_naive(program_id, accounts, data)
+}
+
+#[rule]
+pub fn rule_aliased_accounts_share_state() {
+    let account_infos = cvlr_deserialize_nondet_accounts();
+    let accounts = alias(&account_infos[..2], 1, 0);
+    let (a, b) = (&accounts[0], &accounts[1]);
+
+    let lamports: u64 = nondet();
+    **a.try_borrow_mut_lamports().unwrap() = lamports;
+    clog_acc_info!(a);
+    clog_acc_info!(b);
+
+    cvlr_assert!(a.key == b.key);
+    cvlr_assert_eq!(b.lamports(), lamports);
+    cvlr_assert_eq!(a.data_len(), b.data_len());
+    // -- the data cell is shared, so it cannot be borrowed twice mutably
+    let _data = a.try_borrow_mut_data().unwrap();
+    cvlr_assert!(b.try_borrow_data().is_err());
+}
+
+#[rule]
+pub fn rule_aliased_transfer_checked_conserved() {
+    let account_infos = cvlr_deserialize_nondet_accounts();
+    let accounts = nondet_aliased_accounts(&account_infos[..4]);
+    let harness = InstructionHarness::new(&accounts);
+    check_lamports_conserved(&harness, process_transfer_checked);
+}
+
+/// Expected to be violated: a transfer to the same account mints lamports
+#[rule]
+pub fn rule_aliased_transfer_naive_conserved() {
+    let account_infos = cvlr_deserialize_nondet_accounts();
+    let accounts = alias(&account_infos[..2], 1, 0);
+    let harness = InstructionHarness::new(&accounts);
+    check_lamports_conserved(&harness, process_transfer_naive);
+}
+
+/// A token transfer from an account to itself leaves it unchanged
+#[rule]
+pub fn rule_aliased_spl_token_self_transfer() {
+    let account_infos = cvlr_deserialize_nondet_accounts();
+    let program_id = spl_token::id();
+    // -- source and destination are the same account
+    let accounts = alias(&account_infos[..4], 2, 0);
+    let (src, mint, authority) = (&accounts[0], &accounts[1], &accounts[3]);
+    cvlr_assume!(src.data_len() == Account::LEN);
+    cvlr_assume!(mint.data_len() == Mint::LEN);
+    cvlr_assume!(!(authority.owner == &program_id && authority.data_len() == Multisig::LEN));
+
+    let src_pre = Account::unpack(&src.data.borrow()).unwrap();
+    let decimals = Mint::unpack(&mint.data.borrow()).unwrap().decimals;
+
+    let amount: u64 = nondet();
+    Processor::process_transfer(&program_id, &accounts, amount, Some(decimals)).unwrap();
+
+    let src_post = Account::unpack(&src.data.borrow()).unwrap();
+    clog!(amount, src_pre.amount, src_post.amount);
+
+    cvlr_assert_eq!(src_post.amount, src_pre.amount);
+    cvlr_assert_le!(MathInt::from(amount), MathInt::from(src_pre.amount));
+}
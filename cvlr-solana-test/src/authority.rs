@@ -0,0 +1,88 @@
+//! Detector for missing signer and owner checks
+//!
+//! An instruction may only debit or write an account that either signed the
+//! transaction or is owned by the program. An account that was debited or
+//! written otherwise is logged with `clog_acc_info!` before the assertion
+//! fails, so that the counterexample shows the offending account.
+
+use crate::instruction::{InstructionHarness, ProcessInstruction};
+use crate::lamports::move_lamports;
+use cvlr::prelude::*;
+use cvlr_solana::{clog_acc_info, cvlr_deserialize_nondet_accounts};
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+/// Runs [processor] and checks that every debited or written account was a
+/// signer or owned by the program
+pub fn check_authorized_changes(harness: &InstructionHarness, processor: ProcessInstruction) {
+    harness.process(processor).unwrap();
+    let post = harness.post();
+
+    for ((info, pre), post) in harness.accounts.iter().zip(&harness.pre).zip(&post) {
+        let debited = post.lamports < pre.lamports;
+        let written = post.data != pre.data;
+        let authorized = info.is_signer || pre.owner == harness.program_id;
+        if (debited || written) && !authorized {
+            clog_acc_info!(info);
+        }
+        cvlr_assert!(!(debited || written) || authorized);
+    }
+}
+
+/// Moves the amount in the instruction data from the first to the second
+/// account, optionally checking that the first account may be debited
+fn withdraw(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+    check_authority: bool,
+) -> ProgramResult {
+    let [from, to, ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    if check_authority && !from.is_signer && from.owner != program_id {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    let amount = data
+        .get(..8)
+        .and_then(|bytes| bytes.try_into().ok())
+        .map(u64::from_le_bytes)
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    if from.key == to.key {
+        return Ok(());
+    }
+    move_lamports(from, to, amount)
+}
+
+fn process_withdraw_checked(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    withdraw(program_id, accounts, data, true)
+}
+
+fn process_withdraw_unchecked(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    withdraw(program_id, accounts, data, false)
+}
+
+#[rule]
+pub fn rule_authority_withdraw_checked() {
+    let account_infos = cvlr_deserialize_nondet_accounts();
+    let harness = InstructionHarness::new(&account_infos);
+    check_authorized_changes(&harness, process_withdraw_checked);
+}
+
+/// Expected to be violated: the withdrawal does not check its source
+#[rule]
+pub fn rule_authority_withdraw_unchecked() {
+    let account_infos = cvlr_deserialize_nondet_accounts();
+    let harness = InstructionHarness::new(&account_infos);
+    check_authorized_changes(&harness, process_withdraw_unchecked);
+}
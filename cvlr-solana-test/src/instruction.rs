@@ -0,0 +1,139 @@
+//! Harness for verifying a whole instruction processor
+//!
+//! Supplies a nondet program id, a bounded list of nondet accounts and nondet
+//! instruction data, and invokes a processor with the signature of
+//! `process_instruction`. Every account is snapshotted before the call, so that
+//! rules can compare with a snapshot taken after it.
+
+use cvlr::prelude::*;
+use cvlr_solana::{cvlr_deserialize_nondet_accounts, cvlr_nondet_pubkey};
+use solana_program::{account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey};
+
+/// Largest number of accounts passed to an instruction
+pub const MAX_ACCOUNTS: usize = 8;
+/// Largest length of instruction data
+pub const MAX_INSTRUCTION_DATA: usize = 64;
+
+pub type ProcessInstruction = fn(&Pubkey, &[AccountInfo], &[u8]) -> ProgramResult;
+
+/// State of an account at one point of an instruction
+#[derive(Clone, PartialEq)]
+pub struct AccountSnapshot {
+    pub key: Pubkey,
+    pub lamports: u64,
+    pub owner: Pubkey,
+    pub data: Vec<u8>,
+}
+
+impl AccountSnapshot {
+    pub fn new(info: &AccountInfo) -> Self {
+        Self {
+            key: *info.key,
+            lamports: info.lamports(),
+            owner: *info.owner,
+            data: info.data.borrow().to_vec(),
+        }
+    }
+}
+
+impl cvlr::log::CvlrLog for AccountSnapshot {
+    #[inline(always)]
+    fn log(&self, tag: &str, logger: &mut cvlr::log::CvlrLogger) {
+        use cvlr::log::cvlr_log_with;
+        cvlr_log_with("", &tag, logger);
+        cvlr_log_with("\tlamports", &self.lamports, logger);
+        cvlr_log_with("\tdata_len", &self.data.len(), logger);
+    }
+}
+
+pub struct InstructionHarness<'a> {
+    pub program_id: Pubkey,
+    pub accounts: &'a [AccountInfo<'a>],
+    pub data: Vec<u8>,
+    /// Snapshot of [accounts] before the instruction
+    pub pre: Vec<AccountSnapshot>,
+}
+
+impl<'a> InstructionHarness<'a> {
+    /// Instruction over a nondet prefix of [account_infos]
+    pub fn new(account_infos: &'a [AccountInfo<'a>]) -> Self {
+        let num_accounts: usize = nondet();
+        cvlr_assume!(num_accounts <= MAX_ACCOUNTS);
+        cvlr_assume!(num_accounts <= account_infos.len());
+
+        let data_len: usize = nondet();
+        cvlr_assume!(data_len <= MAX_INSTRUCTION_DATA);
+        let mut data: Vec<u8> = (0..MAX_INSTRUCTION_DATA).map(|_| nondet()).collect();
+        data.truncate(data_len);
+
+        let accounts = &account_infos[..num_accounts];
+        clog!(num_accounts, data_len);
+        Self {
+            program_id: cvlr_nondet_pubkey(),
+            accounts,
+            data,
+            pre: snapshot(accounts),
+        }
+    }
+
+    pub fn process(&self, processor: ProcessInstruction) -> ProgramResult {
+        processor(&self.program_id, self.accounts, &self.data)
+    }
+
+    /// Snapshot of [accounts] now
+    pub fn post(&self) -> Vec<AccountSnapshot> {
+        snapshot(self.accounts)
+    }
+}
+
+pub fn snapshot(accounts: &[AccountInfo]) -> Vec<AccountSnapshot> {
+    accounts.iter().map(AccountSnapshot::new).collect()
+}
+
+fn process_noop(_program_id: &Pubkey, _accounts: &[AccountInfo], _data: &[u8]) -> ProgramResult {
+    Ok(())
+}
+
+/// Writes the instruction data into the accounts owned by the program
+fn process_write_owned(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    for info in accounts.iter().filter(|info| info.owner == program_id) {
+        let mut account_data = info.data.borrow_mut();
+        let n = account_data.len().min(data.len());
+        account_data[..n].copy_from_slice(&data[..n]);
+    }
+    Ok(())
+}
+
+#[rule]
+pub fn rule_instruction_noop_unchanged() {
+    let account_infos = cvlr_deserialize_nondet_accounts();
+    let harness = InstructionHarness::new(&account_infos);
+
+    harness.process(process_noop).unwrap();
+    let post = harness.post();
+
+    cvlr_assert!(harness.pre == post);
+}
+
+/// Only accounts owned by the program are modified
+#[rule]
+pub fn rule_instruction_write_owned_only() {
+    let account_infos = cvlr_deserialize_nondet_accounts();
+    let harness = InstructionHarness::new(&account_infos);
+
+    harness.process(process_write_owned).unwrap();
+    let post = harness.post();
+
+    for (pre, post) in harness.pre.iter().zip(post.iter()) {
+        clog!(pre, post);
+        cvlr_assert_eq!(pre.lamports, post.lamports);
+        cvlr_assert!(pre.owner == post.owner);
+        if pre.owner != harness.program_id {
+            cvlr_assert!(pre.data == post.data);
+        }
+    }
+}
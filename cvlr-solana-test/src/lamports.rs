@@ -0,0 +1,163 @@
+//! Lamport conservation templates for instruction processors
+//!
+//! A processor cannot mint or burn lamports, it can only move them between the
+//! accounts of the instruction. Sums are mathematical integers, so that adding
+//! up lamports of many accounts does not overflow. An account passed more than
+//! once is counted once.
+
+use crate::instruction::{AccountSnapshot, InstructionHarness, ProcessInstruction};
+use cvlr::mathint::NativeInt as MathInt;
+use cvlr::prelude::*;
+use cvlr_solana::cvlr_deserialize_nondet_accounts;
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError,
+    pubkey::Pubkey,
+};
+use std::rc::Rc;
+
+/// True at the first position of every account in [accounts]
+///
+/// A duplicate account shares its lamports cell with an earlier position, and
+/// is counted only once in a sum.
+fn first_occurrences(accounts: &[AccountInfo]) -> Vec<bool> {
+    accounts
+        .iter()
+        .enumerate()
+        .map(|(i, info)| {
+            !accounts[..i]
+                .iter()
+                .any(|other| Rc::ptr_eq(&other.lamports, &info.lamports))
+        })
+        .collect()
+}
+
+/// Total lamports of the distinct accounts selected by [filter], where
+/// [snapshots] are taken from [accounts]
+fn sum_lamports(
+    accounts: &[AccountInfo],
+    snapshots: &[AccountSnapshot],
+    filter: impl Fn(&AccountSnapshot) -> bool,
+) -> MathInt {
+    snapshots
+        .iter()
+        .zip(first_occurrences(accounts))
+        .filter(|(s, first)| *first && filter(s))
+        .fold(MathInt::from(0u64), |acc, (s, _)| acc + s.lamports)
+}
+
+pub fn total_lamports(accounts: &[AccountInfo], snapshots: &[AccountSnapshot]) -> MathInt {
+    sum_lamports(accounts, snapshots, |_| true)
+}
+
+/// Runs [processor] and checks that the total of lamports is unchanged
+pub fn check_lamports_conserved(harness: &InstructionHarness, processor: ProcessInstruction) {
+    harness.process(processor).unwrap();
+    let post = harness.post();
+
+    let total_pre = total_lamports(harness.accounts, &harness.pre);
+    let total_post = total_lamports(harness.accounts, &post);
+    clog!(total_pre, total_post);
+
+    cvlr_assert_eq!(total_pre, total_post);
+}
+
+/// Runs [processor] and checks that lamports leave other accounts only to
+/// [sinks]
+///
+/// Sinks only gain lamports, and exactly what the other accounts lose.
+pub fn check_lamports_to_sinks(
+    harness: &InstructionHarness,
+    processor: ProcessInstruction,
+    sinks: &[Pubkey],
+) {
+    harness.process(processor).unwrap();
+    let post = harness.post();
+
+    let is_sink = |s: &AccountSnapshot| sinks.contains(&s.key);
+    let accounts = harness.accounts;
+    let sinks_pre = sum_lamports(accounts, &harness.pre, is_sink);
+    let sinks_post = sum_lamports(accounts, &post, is_sink);
+    let others_pre = sum_lamports(accounts, &harness.pre, |s| !is_sink(s));
+    let others_post = sum_lamports(accounts, &post, |s| !is_sink(s));
+    clog!(sinks_pre, sinks_post, others_pre, others_post);
+
+    for (pre, post) in harness.pre.iter().zip(post.iter()) {
+        if is_sink(pre) {
+            cvlr_assert_le!(pre.lamports, post.lamports);
+        }
+    }
+    cvlr_assert_le!(others_post, others_pre);
+    cvlr_assert_eq!(others_pre - others_post, sinks_post - sinks_pre);
+}
+
+pub fn move_lamports(from: &AccountInfo, to: &AccountInfo, amount: u64) -> ProgramResult {
+    let from_lamports = from
+        .lamports()
+        .checked_sub(amount)
+        .ok_or(ProgramError::InsufficientFunds)?;
+    let to_lamports = to
+        .lamports()
+        .checked_add(amount)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    **from.try_borrow_mut_lamports()? = from_lamports;
+    **to.try_borrow_mut_lamports()? = to_lamports;
+    Ok(())
+}
+
+/// Moves the amount in the instruction data from the first to the second
+/// account
+fn process_transfer_lamports(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let [from, to, ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    let amount = data
+        .get(..8)
+        .and_then(|bytes| bytes.try_into().ok())
+        .map(u64::from_le_bytes)
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    if from.key == to.key {
+        return Ok(());
+    }
+    move_lamports(from, to, amount)
+}
+
+/// Moves all lamports of accounts owned by the program to the first account
+fn process_sweep(program_id: &Pubkey, accounts: &[AccountInfo], _data: &[u8]) -> ProgramResult {
+    let [dst, sources @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    for src in sources {
+        if src.owner == program_id && src.key != dst.key {
+            move_lamports(src, dst, src.lamports())?;
+        }
+    }
+    Ok(())
+}
+
+#[rule]
+pub fn rule_lamports_conserved_transfer() {
+    let account_infos = cvlr_deserialize_nondet_accounts();
+    let harness = InstructionHarness::new(&account_infos);
+    check_lamports_conserved(&harness, process_transfer_lamports);
+}
+
+#[rule]
+pub fn rule_lamports_conserved_sweep() {
+    let account_infos = cvlr_deserialize_nondet_accounts();
+    let harness = InstructionHarness::new(&account_infos);
+    check_lamports_conserved(&harness, process_sweep);
+}
+
+#[rule]
+pub fn rule_lamports_to_sinks_sweep() {
+    let account_infos = cvlr_deserialize_nondet_accounts();
+    let harness = InstructionHarness::new(&account_infos);
+    cvlr_assume!(!harness.accounts.is_empty());
+
+    let sinks = [*harness.accounts[0].key];
+    check_lamports_to_sinks(&harness, process_sweep, &sinks);
+}
@@ -1,2 +1,11 @@
 mod accounts;
-
+mod token_transfer;
+mod token_2022_fee;
+mod instruction;
+mod lamports;
+mod authority;
+mod aliasing;
+mod realloc;
+mod pda;
+mod sysvars;
+mod rent;
@@ -0,0 +1,222 @@
+//! Rules for program derived addresses
+//!
+//! `Pubkey::create_program_address` and `Pubkey::find_program_address` are
+//! summarised (see `cvlr_summaries_core.txt`) to return a fresh result on
+//! every call, so two derivations of the same seeds need not agree. Rules
+//! therefore run with a ghost map (see [ghost]) that gives equal seeds equal
+//! results, and check the properties that programs rely on when they validate
+//! a PDA: it is re-derived from its seeds and bump, and compared with the key
+//! of the passed account.
+
+use cvlr::prelude::*;
+use cvlr_solana::{clog_acc_info, cvlr_deserialize_nondet_accounts, cvlr_nondet_pubkey};
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+const VAULT_SEED: &[u8] = b"vault";
+
+/// True if [info] is the PDA of [program_id] for [seeds] and [bump]
+pub fn is_pda(info: &AccountInfo, seeds: &[&[u8]], bump: u8, program_id: &Pubkey) -> bool {
+    let bump = [bump];
+    let mut seeds_with_bump = seeds.to_vec();
+    seeds_with_bump.push(&bump);
+    match Pubkey::create_program_address(&seeds_with_bump, program_id) {
+        Ok(pda) => pda == *info.key,
+        Err(_) => false,
+    }
+}
+
+/// Checks that the first account is the vault of the second one, with the
+/// bump in the instruction data
+fn process_validate_vault(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let [vault, owner, ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    let bump = *data.first().ok_or(ProgramError::InvalidInstructionData)?;
+    if !is_pda(vault, &[VAULT_SEED, owner.key.as_ref()], bump, program_id) {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    Ok(())
+}
+
+/// Ghost map over the derivations of a rule
+///
+/// The mocks return a fresh result the first time they see seeds and a
+/// program, like the summaries, and the same result every time after, like the
+/// real derivation. They replace the summarised functions only in rules that
+/// call [setup_pda_ghost] on entry; under `rt` the real functions are called.
+pub mod ghost {
+    use super::*;
+    use solana_program::pubkey::PubkeyError;
+    use std::ptr::addr_of_mut;
+
+    mod rt_decls {
+        extern "C" {
+            pub fn CVT_register_mock_fn(fn_orig: usize, fn_mock: usize);
+        }
+    }
+
+    #[cfg(feature = "rt")]
+    mod rt_impls {
+        #[no_mangle]
+        pub extern "C" fn CVT_register_mock_fn(_fn_orig: usize, _fn_mock: usize) {}
+    }
+
+    type CreateFn = fn(&[&[u8]], &Pubkey) -> Result<Pubkey, PubkeyError>;
+    type FindFn = fn(&[&[u8]], &Pubkey) -> (Pubkey, u8);
+
+    /// Results of `create_program_address`, by seeds and program
+    static mut CREATED: Vec<(Vec<u8>, Pubkey, Result<Pubkey, PubkeyError>)> = Vec::new();
+    /// Results of `find_program_address`, by seeds and program
+    static mut FOUND: Vec<(Vec<u8>, Pubkey, (Pubkey, u8))> = Vec::new();
+
+    /// [seeds] as one byte string, each seed prefixed by its length
+    fn seeds_key(seeds: &[&[u8]]) -> Vec<u8> {
+        let mut key = Vec::new();
+        for seed in seeds {
+            key.extend_from_slice(&(seed.len() as u64).to_le_bytes());
+            key.extend_from_slice(seed);
+        }
+        key
+    }
+
+    /// The result recorded in [log] for [seeds] and [program_id], or [fresh]
+    /// recorded for them
+    fn lookup<T: Clone>(
+        log: &mut Vec<(Vec<u8>, Pubkey, T)>,
+        seeds: &[&[u8]],
+        program_id: &Pubkey,
+        fresh: impl FnOnce() -> T,
+    ) -> T {
+        let key = seeds_key(seeds);
+        if let Some((_, _, res)) = log.iter().find(|(k, p, _)| *k == key && p == program_id) {
+            return res.clone();
+        }
+        let res = fresh();
+        log.push((key, *program_id, res.clone()));
+        res
+    }
+
+    /// Mock of `Pubkey::create_program_address`
+    pub fn create_program_address(
+        seeds: &[&[u8]],
+        program_id: &Pubkey,
+    ) -> Result<Pubkey, PubkeyError> {
+        let created = unsafe { &mut *addr_of_mut!(CREATED) };
+        lookup(created, seeds, program_id, || {
+            if nondet() {
+                Ok(cvlr_nondet_pubkey())
+            } else {
+                Err(PubkeyError::InvalidSeeds)
+            }
+        })
+    }
+
+    /// Mock of `Pubkey::find_program_address`
+    ///
+    /// The address found is the one created with the bump found. Unlike the
+    /// real function, the bump is not necessarily the largest valid one.
+    pub fn find_program_address(seeds: &[&[u8]], program_id: &Pubkey) -> (Pubkey, u8) {
+        let found = unsafe { &mut *addr_of_mut!(FOUND) };
+        lookup(found, seeds, program_id, || {
+            let bump: u8 = nondet();
+            let bump_seed = [bump];
+            let mut seeds_with_bump = seeds.to_vec();
+            seeds_with_bump.push(&bump_seed);
+            let pda = create_program_address(&seeds_with_bump, program_id);
+            cvlr_assume!(pda.is_ok());
+            (pda.unwrap(), bump)
+        })
+    }
+
+    /// Replaces the summarised derivations by the ghost map
+    #[no_mangle]
+    pub fn setup_pda_ghost() {
+        let [create_orig, create_mock]: [CreateFn; 2] =
+            [Pubkey::create_program_address, create_program_address];
+        let [find_orig, find_mock]: [FindFn; 2] =
+            [Pubkey::find_program_address, find_program_address];
+        unsafe {
+            rt_decls::CVT_register_mock_fn(create_orig as usize, create_mock as usize);
+            rt_decls::CVT_register_mock_fn(find_orig as usize, find_mock as usize);
+        }
+    }
+}
+
+/// The validation rejects every account that is not the vault
+#[rule]
+#[cvlr_hook_on_entry(ghost::setup_pda_ghost())]
+pub fn rule_pda_validation_rejects_mismatch() {
+    let account_infos = cvlr_deserialize_nondet_accounts();
+    let program_id = cvlr_nondet_pubkey();
+    let (vault, owner) = (&account_infos[0], &account_infos[1]);
+    clog_acc_info!(vault);
+    clog_acc_info!(owner);
+
+    let bump: u8 = nondet();
+    let expected =
+        Pubkey::create_program_address(&[VAULT_SEED, owner.key.as_ref(), &[bump]], &program_id);
+    cvlr_assume!(expected != Ok(*vault.key));
+
+    let res = process_validate_vault(&program_id, &account_infos[..2], &[bump]);
+
+    cvlr_assert!(res == Err(ProgramError::InvalidSeeds));
+}
+
+/// The validation accepts the vault
+#[rule]
+#[cvlr_hook_on_entry(ghost::setup_pda_ghost())]
+pub fn rule_pda_validation_accepts_match() {
+    let account_infos = cvlr_deserialize_nondet_accounts();
+    let program_id = cvlr_nondet_pubkey();
+    let (vault, owner) = (&account_infos[0], &account_infos[1]);
+
+    let (pda, bump) = Pubkey::find_program_address(&[VAULT_SEED, owner.key.as_ref()], &program_id);
+    cvlr_assume!(*vault.key == pda);
+    clog!(bump);
+
+    let res = process_validate_vault(&program_id, &account_infos[..2], &[bump]);
+
+    cvlr_assert!(res.is_ok());
+}
+
+/// Equal seeds give equal results
+#[rule]
+#[cvlr_hook_on_entry(ghost::setup_pda_ghost())]
+pub fn rule_pda_derivation_deterministic() {
+    let program_id = cvlr_nondet_pubkey();
+    let owner = cvlr_nondet_pubkey();
+    let seeds: &[&[u8]] = &[VAULT_SEED, owner.as_ref()];
+
+    let (pda1, bump1) = Pubkey::find_program_address(seeds, &program_id);
+    let (pda2, bump2) = Pubkey::find_program_address(seeds, &program_id);
+    clog!(bump1, bump2);
+
+    cvlr_assert!(pda1 == pda2);
+    cvlr_assert_eq!(bump1, bump2);
+
+    let bump: u8 = nondet();
+    let res1 = Pubkey::create_program_address(&[VAULT_SEED, owner.as_ref(), &[bump]], &program_id);
+    let res2 = Pubkey::create_program_address(&[VAULT_SEED, owner.as_ref(), &[bump]], &program_id);
+    cvlr_assert!(res1 == res2);
+}
+
+/// The address found for seeds is the one created with its bump
+#[rule]
+#[cvlr_hook_on_entry(ghost::setup_pda_ghost())]
+pub fn rule_pda_find_matches_create() {
+    let program_id = cvlr_nondet_pubkey();
+    let owner = cvlr_nondet_pubkey();
+
+    let (pda, bump) = Pubkey::find_program_address(&[VAULT_SEED, owner.as_ref()], &program_id);
+    let res = Pubkey::create_program_address(&[VAULT_SEED, owner.as_ref(), &[bump]], &program_id);
+    clog!(bump);
+
+    cvlr_assert!(res == Ok(pda));
+}
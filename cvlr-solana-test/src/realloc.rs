@@ -0,0 +1,146 @@
+//! Rules for the contract of `AccountInfo::realloc`
+//!
+//! Growing keeps existing bytes and, with `zero_init`, zeroes the new ones.
+//! The length may grow by at most `MAX_PERMITTED_DATA_INCREASE` over the
+//! length the account had when the instruction started, no matter how often it
+//! is reallocated in between.
+
+use cvlr::prelude::*;
+use cvlr_solana::{clog_acc_info, cvlr_new_account_info};
+use solana_program::{
+    account_info::AccountInfo, entrypoint::MAX_PERMITTED_DATA_INCREASE, program_error::ProgramError,
+};
+
+fn byte_at(info: &AccountInfo, i: usize) -> u8 {
+    info.data.borrow()[i]
+}
+
+#[rule]
+pub fn rule_realloc_grow_preserves_data() {
+    let acc = cvlr_new_account_info();
+    let old_len = acc.data_len();
+    let i: usize = nondet();
+    cvlr_assume!(i < old_len);
+    let byte = byte_at(&acc, i);
+
+    let new_len: usize = nondet();
+    let zero_init: bool = nondet();
+    cvlr_assume!(old_len < new_len);
+    acc.realloc(new_len, zero_init).unwrap();
+    clog_acc_info!(&acc);
+    clog!(old_len, new_len, zero_init, i, byte);
+
+    cvlr_assert_eq!(acc.data_len(), new_len);
+    cvlr_assert_eq!(byte_at(&acc, i), byte);
+}
+
+#[rule]
+pub fn rule_realloc_grow_zero_init() {
+    let acc = cvlr_new_account_info();
+    let old_len = acc.data_len();
+
+    let new_len: usize = nondet();
+    cvlr_assume!(old_len < new_len);
+    acc.realloc(new_len, true).unwrap();
+
+    let i: usize = nondet();
+    cvlr_assume!(old_len <= i && i < new_len);
+    clog_acc_info!(&acc);
+    clog!(old_len, new_len, i);
+
+    cvlr_assert_eq!(byte_at(&acc, i), 0);
+}
+
+/// Shrinking keeps the prefix, and growing back with `zero_init` does not
+/// resurrect the truncated bytes
+#[rule]
+pub fn rule_realloc_shrink_then_grow() {
+    let acc = cvlr_new_account_info();
+    let old_len = acc.data_len();
+
+    let short_len: usize = nondet();
+    cvlr_assume!(short_len < old_len);
+    let i: usize = nondet();
+    cvlr_assume!(i < old_len);
+    let byte = byte_at(&acc, i);
+
+    acc.realloc(short_len, false).unwrap();
+    cvlr_assert_eq!(acc.data_len(), short_len);
+
+    acc.realloc(old_len, true).unwrap();
+    clog_acc_info!(&acc);
+    clog!(old_len, short_len, i, byte);
+
+    cvlr_assert_eq!(acc.data_len(), old_len);
+    if i < short_len {
+        cvlr_assert_eq!(byte_at(&acc, i), byte);
+    } else {
+        cvlr_assert_eq!(byte_at(&acc, i), 0);
+    }
+}
+
+#[rule]
+pub fn rule_realloc_same_len_noop() {
+    let acc = cvlr_new_account_info();
+    let len = acc.data_len();
+    let i: usize = nondet();
+    cvlr_assume!(i < len);
+    let byte = byte_at(&acc, i);
+
+    acc.realloc(len, true).unwrap();
+    clog!(len, i, byte);
+
+    cvlr_assert_eq!(acc.data_len(), len);
+    cvlr_assert_eq!(byte_at(&acc, i), byte);
+}
+
+/// Growing fails exactly when it exceeds the original length by more than
+/// `MAX_PERMITTED_DATA_INCREASE`
+#[rule]
+pub fn rule_realloc_max_increase() {
+    let acc = cvlr_new_account_info();
+    let old_len = acc.data_len();
+
+    let new_len: usize = nondet();
+    let zero_init: bool = nondet();
+    let res = acc.realloc(new_len, zero_init);
+    clog!(old_len, new_len, zero_init);
+
+    if new_len <= old_len + MAX_PERMITTED_DATA_INCREASE {
+        cvlr_assert!(res.is_ok());
+        cvlr_assert_eq!(acc.data_len(), new_len);
+    } else {
+        cvlr_assert!(res == Err(ProgramError::InvalidRealloc));
+        cvlr_assert_eq!(acc.data_len(), old_len);
+    }
+}
+
+/// The limit is relative to the original length, so it cannot be bypassed by
+/// growing in steps
+#[rule]
+pub fn rule_realloc_max_increase_in_steps() {
+    let acc = cvlr_new_account_info();
+    let old_len = acc.data_len();
+
+    acc.realloc(old_len + MAX_PERMITTED_DATA_INCREASE, false)
+        .unwrap();
+    let res = acc.realloc(old_len + MAX_PERMITTED_DATA_INCREASE + 1, false);
+    clog!(old_len);
+
+    cvlr_assert!(res == Err(ProgramError::InvalidRealloc));
+    cvlr_assert_eq!(acc.data_len(), old_len + MAX_PERMITTED_DATA_INCREASE);
+}
+
+/// realloc needs to borrow the data mutably
+#[rule]
+pub fn rule_realloc_while_borrowed() {
+    let acc = cvlr_new_account_info();
+    let old_len = acc.data_len();
+    let _data = acc.data.borrow();
+
+    let new_len: usize = nondet();
+    cvlr_assume!(new_len != old_len);
+    let res = acc.realloc(new_len, true);
+
+    cvlr_assert!(res == Err(ProgramError::AccountBorrowFailed));
+}
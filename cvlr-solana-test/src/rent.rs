@@ -0,0 +1,171 @@
+//! Rent-exemption invariant for instruction processors
+//!
+//! An account that a processor creates or reallocates must end up rent-exempt,
+//! and an account that was rent-exempt must stay so, unless it is closed. This
+//! catches programs that grow data without topping up lamports.
+
+use crate::instruction::InstructionHarness;
+use crate::lamports::move_lamports;
+use cvlr::prelude::*;
+use cvlr_solana::{clog_acc_info, cvlr_deserialize_nondet_accounts, cvlr_new_account_info};
+use cvlr_sysvars::{cvlr_nondet_rent, is_rent_exempt, minimum_balance, Rent};
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError,
+    pubkey::Pubkey, system_instruction::MAX_PERMITTED_DATA_LENGTH,
+};
+
+/// Processor that is given the rent, as a program reads it with `Rent::get`
+pub type ProcessWithRent = fn(&Rent, &Pubkey, &[AccountInfo], &[u8]) -> ProgramResult;
+
+/// Length of the data after growing, as in `init_accounts`
+const GROWN_LEN: usize = 2 * 1024;
+
+/// Runs [processor] and checks that created, reallocated and previously
+/// rent-exempt accounts are rent-exempt, unless they are closed
+pub fn check_rent_exempt(harness: &InstructionHarness, rent: &Rent, processor: ProcessWithRent) {
+    processor(rent, &harness.program_id, harness.accounts, &harness.data).unwrap();
+    let post = harness.post();
+
+    for ((info, pre), post) in harness.accounts.iter().zip(&harness.pre).zip(&post) {
+        let created = pre.lamports == 0;
+        let reallocated = post.data.len() != pre.data.len();
+        let was_exempt = is_rent_exempt(rent, pre.lamports, pre.data.len());
+        let must_be_exempt = (created || reallocated || was_exempt) && post.lamports > 0;
+        let is_exempt = is_rent_exempt(rent, post.lamports, post.data.len());
+        if must_be_exempt && !is_exempt {
+            clog_acc_info!(info);
+        }
+        cvlr_assert!(!must_be_exempt || is_exempt);
+    }
+}
+
+/// Grows the first account to [GROWN_LEN] without adding lamports
+fn process_grow_unfunded(
+    _rent: &Rent,
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _data: &[u8],
+) -> ProgramResult {
+    let [acc, ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    acc.realloc(GROWN_LEN, true)
+}
+
+/// Sets the length of the first account to [len], and tops it up to the
+/// minimum balance from the second account
+fn resize_funded(rent: &Rent, accounts: &[AccountInfo], len: usize) -> ProgramResult {
+    let [acc, payer, ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    if !payer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if acc.key == payer.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+    acc.realloc(len, true)?;
+
+    let top_up = minimum_balance(rent, len).saturating_sub(acc.lamports());
+    // -- the payer must stay rent-exempt itself
+    let payer_left = payer
+        .lamports()
+        .checked_sub(top_up)
+        .ok_or(ProgramError::InsufficientFunds)?;
+    if !is_rent_exempt(rent, payer_left, payer.data_len()) {
+        return Err(ProgramError::InsufficientFunds);
+    }
+    move_lamports(payer, acc, top_up)
+}
+
+/// Grows the first account to [GROWN_LEN], paid by the second account
+fn process_grow_funded(
+    rent: &Rent,
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _data: &[u8],
+) -> ProgramResult {
+    resize_funded(rent, accounts, GROWN_LEN)
+}
+
+/// Creates the first account with the length in the instruction data, paid by
+/// the second account
+fn process_create(
+    rent: &Rent,
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let [acc, ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    if acc.lamports() != 0 || acc.data_len() != 0 {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+    let len = data
+        .get(..2)
+        .and_then(|bytes| bytes.try_into().ok())
+        .map(u16::from_le_bytes)
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    resize_funded(rent, accounts, len as usize)
+}
+
+#[rule]
+pub fn rule_rent_exempt_grow_funded() {
+    let account_infos = cvlr_deserialize_nondet_accounts();
+    let harness = InstructionHarness::new(&account_infos);
+    let rent = cvlr_nondet_rent();
+    check_rent_exempt(&harness, &rent, process_grow_funded);
+}
+
+/// Expected to be violated: growing to `2 * 1024` bytes needs more lamports
+#[rule]
+pub fn rule_rent_exempt_grow_unfunded() {
+    let account_infos = cvlr_deserialize_nondet_accounts();
+    let harness = InstructionHarness::new(&account_infos);
+    let rent = cvlr_nondet_rent();
+    check_rent_exempt(&harness, &rent, process_grow_unfunded);
+}
+
+#[rule]
+pub fn rule_rent_exempt_create() {
+    let account_infos = cvlr_deserialize_nondet_accounts();
+    let harness = InstructionHarness::new(&account_infos);
+    let rent = cvlr_nondet_rent();
+    check_rent_exempt(&harness, &rent, process_create);
+}
+
+/// The `init_accounts` path: an account that was rent-exempt before growing is
+/// not necessarily rent-exempt after it
+#[rule]
+pub fn rule_rent_realloc_needs_top_up() {
+    let acc = cvlr_new_account_info();
+    let rent = cvlr_nondet_rent();
+    cvlr_assume!(acc.data_len() < GROWN_LEN);
+    cvlr_assume!(is_rent_exempt(&rent, acc.lamports(), acc.data_len()));
+
+    acc.realloc(GROWN_LEN, true).unwrap();
+    clog_acc_info!(&acc);
+    clog!(
+        rent.lamports_per_byte_year,
+        minimum_balance(&rent, GROWN_LEN)
+    );
+
+    cvlr_satisfy!(!is_rent_exempt(&rent, acc.lamports(), acc.data_len()));
+}
+
+/// Larger accounts need at least as many lamports
+#[rule]
+pub fn rule_rent_minimum_balance_monotone() {
+    let rent = cvlr_nondet_rent();
+    let len1: usize = nondet();
+    let len2: usize = nondet();
+    cvlr_assume!(len1 <= len2);
+    cvlr_assume!(len2 as u64 <= MAX_PERMITTED_DATA_LENGTH);
+
+    let min1 = minimum_balance(&rent, len1);
+    let min2 = minimum_balance(&rent, len2);
+    clog!(rent.lamports_per_byte_year, len1, len2, min1, min2);
+
+    cvlr_assert_le!(min1, min2);
+}
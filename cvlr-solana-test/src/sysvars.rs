@@ -0,0 +1,49 @@
+//! Rules for the nondet sysvars of `cvlr-sysvars`
+
+use cvlr::prelude::*;
+use cvlr_sysvars::{
+    checked_minimum_balance, cvlr_nondet_clock, cvlr_nondet_clock_after,
+    cvlr_nondet_epoch_schedule, cvlr_nondet_rent,
+};
+use solana_program::system_instruction::MAX_PERMITTED_DATA_LENGTH;
+
+#[rule]
+pub fn rule_sysvars_clock_epoch_bounds() {
+    let schedule = cvlr_nondet_epoch_schedule();
+    let clock = cvlr_nondet_clock(&schedule);
+    clog!(clock.slot, clock.epoch, clock.leader_schedule_epoch);
+
+    // -- the slot is within its epoch
+    let first = schedule.get_first_slot_in_epoch(clock.epoch);
+    let last = schedule.get_last_slot_in_epoch(clock.epoch);
+    cvlr_assert_le!(first, clock.slot);
+    cvlr_assert_le!(clock.slot, last);
+    // -- the leader schedule is known at most one epoch ahead
+    cvlr_assert_le!(clock.epoch, clock.leader_schedule_epoch);
+    cvlr_assert_le!(clock.leader_schedule_epoch, clock.epoch + 1);
+}
+
+#[rule]
+pub fn rule_sysvars_clock_after_monotone() {
+    let schedule = cvlr_nondet_epoch_schedule();
+    let c1 = cvlr_nondet_clock(&schedule);
+    let c2 = cvlr_nondet_clock_after(&c1, &schedule);
+    clog!(c1.slot, c1.epoch, c1.unix_timestamp);
+    clog!(c2.slot, c2.epoch, c2.unix_timestamp);
+
+    cvlr_assert_le!(c1.epoch, c2.epoch);
+    cvlr_assert_le!(c1.leader_schedule_epoch, c2.leader_schedule_epoch);
+    cvlr_assert_le!(c1.epoch_start_timestamp, c2.epoch_start_timestamp);
+}
+
+#[rule]
+pub fn rule_sysvars_rent_no_overflow() {
+    let rent = cvlr_nondet_rent();
+    let data_len: usize = nondet();
+    cvlr_assume!(data_len as u64 <= MAX_PERMITTED_DATA_LENGTH);
+
+    let min = checked_minimum_balance(&rent, data_len);
+    clog!(rent.lamports_per_byte_year, data_len, min);
+
+    cvlr_assert!(min.is_some());
+}
@@ -0,0 +1,279 @@
+//! Rules for the Token-2022 transfer fee extension
+//!
+//! Checks the fee arithmetic of the mint's `TransferFeeConfig` against real
+//! account state: a transfer withholds exactly the computed fee on the
+//! destination, and moving withheld tokens (harvest and withdraw) neither mints
+//! nor burns.
+
+use cvlr::mathint::NativeInt as MathInt;
+use cvlr::prelude::*;
+use cvlr_solana::{clog_acc_info, cvlr_deserialize_nondet_accounts};
+use solana_program::{account_info::AccountInfo, program_pack::Pack, pubkey::Pubkey};
+use spl_token_2022::{
+    extension::{
+        transfer_fee::{instruction, TransferFeeAmount, TransferFeeConfig},
+        BaseStateWithExtensions, StateWithExtensions,
+    },
+    processor::Processor,
+    state::{Account, Mint, Multisig},
+};
+
+/// Accounts of a `TransferCheckedWithFee` instruction, in instruction order
+///
+/// The authority is a single signer, not a multisig.
+fn nondet_transfer_accounts<'a>(
+    account_infos: &'a [AccountInfo<'a>],
+    program_id: &Pubkey,
+) -> &'a [AccountInfo<'a>] {
+    let accounts = &account_infos[..4];
+    let (src, mint, dst, authority) = (&accounts[0], &accounts[1], &accounts[2], &accounts[3]);
+
+    assume_single_signer(authority, program_id);
+
+    clog_acc_info!(src);
+    clog_acc_info!(mint);
+    clog_acc_info!(dst);
+    clog_acc_info!(authority);
+    accounts
+}
+
+fn assume_single_signer(authority: &AccountInfo, program_id: &Pubkey) {
+    cvlr_assume!(!(authority.owner == program_id && authority.data_len() == Multisig::LEN));
+}
+
+fn token_amount(info: &AccountInfo) -> u64 {
+    let data = info.data.borrow();
+    StateWithExtensions::<Account>::unpack(&data)
+        .unwrap()
+        .base
+        .amount
+}
+
+/// Fees withheld on a token account, zero without the extension
+fn account_withheld(info: &AccountInfo) -> u64 {
+    let data = info.data.borrow();
+    let account = StateWithExtensions::<Account>::unpack(&data).unwrap();
+    account
+        .get_extension::<TransferFeeAmount>()
+        .map_or(0, |ext| ext.withheld_amount.into())
+}
+
+fn mint_decimals(info: &AccountInfo) -> u8 {
+    let data = info.data.borrow();
+    StateWithExtensions::<Mint>::unpack(&data)
+        .unwrap()
+        .base
+        .decimals
+}
+
+fn mint_fee_config(info: &AccountInfo) -> TransferFeeConfig {
+    let data = info.data.borrow();
+    let mint = StateWithExtensions::<Mint>::unpack(&data).unwrap();
+    *mint.get_extension::<TransferFeeConfig>().unwrap()
+}
+
+/// Fees harvested to the mint
+fn mint_withheld(info: &AccountInfo) -> u64 {
+    mint_fee_config(info).withheld_amount.into()
+}
+
+#[rule]
+pub fn rule_token_2022_transfer_fee_withheld() {
+    let account_infos = cvlr_deserialize_nondet_accounts();
+    let program_id = spl_token_2022::id();
+    let accounts = nondet_transfer_accounts(&account_infos, &program_id);
+    let (src, mint, dst) = (&accounts[0], &accounts[1], &accounts[2]);
+
+    let config = mint_fee_config(mint);
+    let decimals = mint_decimals(mint);
+    let dst_withheld_pre = account_withheld(dst);
+
+    // -- no pending fee change, so every epoch selects the same fee
+    let transfer_fee = config.newer_transfer_fee;
+    cvlr_assume!(config.older_transfer_fee == transfer_fee);
+
+    let amount: u64 = nondet();
+    let fee: u64 = nondet();
+    Processor::process_transfer(&program_id, accounts, amount, Some(decimals), Some(fee)).unwrap();
+
+    let dst_withheld_post = account_withheld(dst);
+
+    clog!(amount, fee, dst_withheld_pre, dst_withheld_post);
+
+    // -- the fee is exactly the one computed by the mint
+    cvlr_assert!(transfer_fee.calculate_fee(amount) == Some(fee));
+    cvlr_assert_le!(fee, amount);
+    cvlr_assert_le!(fee, u64::from(transfer_fee.maximum_fee));
+
+    if src.key != dst.key {
+        // -- the fee accumulates on the destination
+        cvlr_assert_eq!(
+            MathInt::from(dst_withheld_post),
+            MathInt::from(dst_withheld_pre) + fee
+        );
+    } else {
+        cvlr_assert_eq!(dst_withheld_post, dst_withheld_pre);
+    }
+}
+
+#[rule]
+pub fn rule_token_2022_transfer_fee_conservation() {
+    let account_infos = cvlr_deserialize_nondet_accounts();
+    let program_id = spl_token_2022::id();
+    let accounts = nondet_transfer_accounts(&account_infos, &program_id);
+    let (src, mint, dst) = (&accounts[0], &accounts[1], &accounts[2]);
+    cvlr_assume!(src.key != dst.key);
+
+    let decimals = mint_decimals(mint);
+    let src_pre = token_amount(src);
+    let dst_pre = token_amount(dst);
+    let src_withheld_pre = account_withheld(src);
+    let dst_withheld_pre = account_withheld(dst);
+
+    let amount: u64 = nondet();
+    let fee: u64 = nondet();
+    Processor::process_transfer(&program_id, accounts, amount, Some(decimals), Some(fee)).unwrap();
+
+    let src_post = token_amount(src);
+    let dst_post = token_amount(dst);
+    let src_withheld_post = account_withheld(src);
+    let dst_withheld_post = account_withheld(dst);
+
+    clog!(amount, fee, src_pre, dst_pre, src_post, dst_post);
+
+    cvlr_assert_eq!(
+        MathInt::from(src_post),
+        MathInt::from(src_pre) - MathInt::from(amount)
+    );
+    // -- the destination is credited the amount net of the fee
+    cvlr_assert_eq!(
+        MathInt::from(dst_post),
+        MathInt::from(dst_pre) + MathInt::from(amount) - MathInt::from(fee)
+    );
+    cvlr_assert_eq!(src_withheld_post, src_withheld_pre);
+    cvlr_assert_eq!(
+        MathInt::from(src_pre) + MathInt::from(dst_pre) + MathInt::from(dst_withheld_pre),
+        MathInt::from(src_post) + MathInt::from(dst_post) + MathInt::from(dst_withheld_post)
+    );
+}
+
+/// Harvesting moves withheld fees from token accounts to the mint
+#[rule]
+pub fn rule_token_2022_harvest_conservation() {
+    let account_infos = cvlr_deserialize_nondet_accounts();
+    let program_id = spl_token_2022::id();
+    let accounts = &account_infos[..3];
+    let (mint, a, b) = (&accounts[0], &accounts[1], &accounts[2]);
+    cvlr_assume!(a.key != b.key);
+    clog_acc_info!(mint);
+    clog_acc_info!(a);
+    clog_acc_info!(b);
+
+    let mint_pre = mint_withheld(mint);
+    let a_pre = account_withheld(a);
+    let b_pre = account_withheld(b);
+
+    let ix = instruction::harvest_withheld_tokens_to_mint(&program_id, mint.key, &[a.key, b.key])
+        .unwrap();
+    Processor::process(&program_id, accounts, &ix.data).unwrap();
+
+    let mint_post = mint_withheld(mint);
+    let a_post = account_withheld(a);
+    let b_post = account_withheld(b);
+
+    clog!(mint_pre, a_pre, b_pre, mint_post, a_post, b_post);
+
+    cvlr_assert_eq!(
+        MathInt::from(mint_pre) + MathInt::from(a_pre) + MathInt::from(b_pre),
+        MathInt::from(mint_post) + MathInt::from(a_post) + MathInt::from(b_post)
+    );
+}
+
+#[rule]
+pub fn rule_token_2022_withdraw_withheld_from_mint_conservation() {
+    let account_infos = cvlr_deserialize_nondet_accounts();
+    let program_id = spl_token_2022::id();
+    let accounts = &account_infos[..3];
+    let (mint, dst, authority) = (&accounts[0], &accounts[1], &accounts[2]);
+    assume_single_signer(authority, &program_id);
+    clog_acc_info!(mint);
+    clog_acc_info!(dst);
+    clog_acc_info!(authority);
+
+    let mint_pre = mint_withheld(mint);
+    let dst_pre = token_amount(dst);
+
+    let ix = instruction::withdraw_withheld_tokens_from_mint(
+        &program_id,
+        mint.key,
+        dst.key,
+        authority.key,
+        &[],
+    )
+    .unwrap();
+    Processor::process(&program_id, accounts, &ix.data).unwrap();
+
+    let mint_post = mint_withheld(mint);
+    let dst_post = token_amount(dst);
+
+    clog!(mint_pre, dst_pre, mint_post, dst_post);
+
+    cvlr_assert!(authority.is_signer);
+    cvlr_assert_eq!(mint_post, 0);
+    cvlr_assert_eq!(
+        MathInt::from(mint_pre) + MathInt::from(dst_pre),
+        MathInt::from(mint_post) + MathInt::from(dst_post)
+    );
+}
+
+#[rule]
+pub fn rule_token_2022_withdraw_withheld_from_accounts_conservation() {
+    let account_infos = cvlr_deserialize_nondet_accounts();
+    let program_id = spl_token_2022::id();
+    let accounts = &account_infos[..4];
+    let (mint, dst, authority, src) = (&accounts[0], &accounts[1], &accounts[2], &accounts[3]);
+    assume_single_signer(authority, &program_id);
+    clog_acc_info!(mint);
+    clog_acc_info!(dst);
+    clog_acc_info!(authority);
+    clog_acc_info!(src);
+
+    let self_harvest = src.key == dst.key;
+    let dst_pre = token_amount(dst);
+    let dst_withheld_pre = account_withheld(dst);
+    let src_withheld_pre = if self_harvest {
+        0
+    } else {
+        account_withheld(src)
+    };
+
+    let ix = instruction::withdraw_withheld_tokens_from_accounts(
+        &program_id,
+        mint.key,
+        dst.key,
+        authority.key,
+        &[],
+        &[src.key],
+    )
+    .unwrap();
+    Processor::process(&program_id, accounts, &ix.data).unwrap();
+
+    let dst_post = token_amount(dst);
+    let dst_withheld_post = account_withheld(dst);
+    let src_withheld_post = if self_harvest {
+        0
+    } else {
+        account_withheld(src)
+    };
+
+    clog!(self_harvest, dst_pre, dst_withheld_pre, src_withheld_pre);
+    clog!(dst_post, dst_withheld_post, src_withheld_post);
+
+    cvlr_assert!(authority.is_signer);
+    cvlr_assert_eq!(
+        MathInt::from(dst_pre) + MathInt::from(dst_withheld_pre) + MathInt::from(src_withheld_pre),
+        MathInt::from(dst_post)
+            + MathInt::from(dst_withheld_post)
+            + MathInt::from(src_withheld_post)
+    );
+}
@@ -0,0 +1,146 @@
+use cvlr::mathint::NativeInt as MathInt;
+use cvlr::prelude::*;
+use cvlr_solana::{clog_acc_info, cvlr_deserialize_nondet_accounts};
+use solana_program::{
+    account_info::AccountInfo, program_option::COption, program_pack::Pack, pubkey::Pubkey,
+};
+use spl_token::{
+    processor::Processor,
+    state::{Account, Mint, Multisig},
+};
+
+/// Accounts of a `TransferChecked` instruction, in instruction order
+///
+/// Token accounts and the mint have the size of their state, so that they
+/// unpack. The authority is a single signer, not a multisig.
+fn nondet_transfer_accounts<'a>(
+    account_infos: &'a [AccountInfo<'a>],
+    program_id: &Pubkey,
+) -> &'a [AccountInfo<'a>] {
+    let accounts = &account_infos[..4];
+    let (src, mint, dst, authority) = (&accounts[0], &accounts[1], &accounts[2], &accounts[3]);
+
+    cvlr_assume!(src.data_len() == Account::LEN);
+    cvlr_assume!(dst.data_len() == Account::LEN);
+    cvlr_assume!(mint.data_len() == Mint::LEN);
+    cvlr_assume!(!(authority.owner == program_id && authority.data_len() == Multisig::LEN));
+
+    clog_acc_info!(src);
+    clog_acc_info!(mint);
+    clog_acc_info!(dst);
+    clog_acc_info!(authority);
+    accounts
+}
+
+fn unpack_account(info: &AccountInfo) -> Account {
+    Account::unpack(&info.data.borrow()).unwrap()
+}
+
+fn unpack_mint(info: &AccountInfo) -> Mint {
+    Mint::unpack(&info.data.borrow()).unwrap()
+}
+
+#[rule]
+pub fn rule_spl_token_transfer_conservation() {
+    let account_infos = cvlr_deserialize_nondet_accounts();
+    let program_id = spl_token::id();
+    let accounts = nondet_transfer_accounts(&account_infos, &program_id);
+    let (src, mint, dst) = (&accounts[0], &accounts[1], &accounts[2]);
+
+    let src_pre = unpack_account(src);
+    let dst_pre = unpack_account(dst);
+    let decimals = unpack_mint(mint).decimals;
+
+    let amount: u64 = nondet();
+    Processor::process_transfer(&program_id, accounts, amount, Some(decimals)).unwrap();
+
+    let src_post = unpack_account(src);
+    let dst_post = unpack_account(dst);
+
+    clog!(
+        amount,
+        src_pre.amount,
+        dst_pre.amount,
+        src_post.amount,
+        dst_post.amount
+    );
+
+    cvlr_assert_eq!(
+        MathInt::from(src_pre.amount) + MathInt::from(dst_pre.amount),
+        MathInt::from(src_post.amount) + MathInt::from(dst_post.amount)
+    );
+    if src.key == dst.key {
+        // -- self-transfer is a no-op
+        cvlr_assert_eq!(src_post.amount, src_pre.amount);
+    } else {
+        cvlr_assert_eq!(
+            MathInt::from(src_post.amount),
+            MathInt::from(src_pre.amount) - MathInt::from(amount)
+        );
+        cvlr_assert_eq!(
+            MathInt::from(dst_post.amount),
+            MathInt::from(dst_pre.amount) + amount
+        );
+    }
+}
+
+#[rule]
+pub fn rule_spl_token_transfer_supply_unchanged() {
+    let account_infos = cvlr_deserialize_nondet_accounts();
+    let program_id = spl_token::id();
+    let accounts = nondet_transfer_accounts(&account_infos, &program_id);
+    let (src, mint) = (&accounts[0], &accounts[1]);
+
+    let mint_pre = unpack_mint(mint);
+
+    let amount: u64 = nondet();
+    Processor::process_transfer(&program_id, accounts, amount, Some(mint_pre.decimals)).unwrap();
+
+    let mint_post = unpack_mint(mint);
+    let src_post = unpack_account(src);
+
+    clog!(amount, mint_pre.supply, mint_post.supply);
+
+    cvlr_assert_eq!(mint_post.supply, mint_pre.supply);
+    // -- the mint is the one of the transferred tokens
+    cvlr_assert!(*mint.key == src_post.mint);
+}
+
+#[rule]
+pub fn rule_spl_token_transfer_authority() {
+    let account_infos = cvlr_deserialize_nondet_accounts();
+    let program_id = spl_token::id();
+    let accounts = nondet_transfer_accounts(&account_infos, &program_id);
+    let (src, mint, dst, authority) = (&accounts[0], &accounts[1], &accounts[2], &accounts[3]);
+
+    let src_pre = unpack_account(src);
+    let dst_pre = unpack_account(dst);
+    let decimals = unpack_mint(mint).decimals;
+
+    let amount: u64 = nondet();
+    Processor::process_transfer(&program_id, accounts, amount, Some(decimals)).unwrap();
+
+    let src_post = unpack_account(src);
+
+    clog!(amount, src_pre.delegated_amount, src_post.delegated_amount);
+
+    cvlr_assert!(authority.is_signer);
+    cvlr_assert!(!src_pre.is_frozen());
+    cvlr_assert!(!dst_pre.is_frozen());
+
+    let is_delegate = src_pre.delegate == COption::Some(*authority.key);
+    cvlr_assert!(is_delegate || *authority.key == src_pre.owner);
+    if is_delegate {
+        // -- a delegate spends from its allowance
+        cvlr_assert_le!(amount, src_pre.delegated_amount);
+        if src.key != dst.key {
+            cvlr_assert_eq!(
+                MathInt::from(src_post.delegated_amount),
+                MathInt::from(src_pre.delegated_amount) - MathInt::from(amount)
+            );
+        }
+    } else {
+        // -- the owner does not touch the allowance
+        cvlr_assert_eq!(src_post.delegated_amount, src_pre.delegated_amount);
+    }
+}
@@ -0,0 +1,99 @@
+//! Nondet sysvars for rules
+//!
+//! Sysvars are built from nondet values constrained to what a cluster can
+//! produce: the epoch of a clock agrees with the epoch schedule, a later clock
+//! never goes back in slot, epoch or time, and rent parameters are in a range
+//! where [minimum_balance] does not overflow.
+
+use cvlr::prelude::*;
+pub use solana_program::{
+    clock::{Clock, DEFAULT_SLOTS_PER_EPOCH},
+    epoch_schedule::{EpochSchedule, MINIMUM_SLOTS_PER_EPOCH},
+    rent::{Rent, ACCOUNT_STORAGE_OVERHEAD, DEFAULT_EXEMPTION_THRESHOLD},
+};
+
+/// Largest rent rate of a nondet [Rent]
+///
+/// Large enough for any real cluster, and small enough that the minimum
+/// balance of an account with up to `MAX_PERMITTED_DATA_LENGTH` bytes does not
+/// overflow.
+pub const MAX_LAMPORTS_PER_BYTE_YEAR: u64 = u32::MAX as u64;
+
+/// Epoch schedule without warmup, as on mainnet
+pub fn cvlr_nondet_epoch_schedule() -> EpochSchedule {
+    let slots_per_epoch: u64 = nondet();
+    let leader_schedule_slot_offset: u64 = nondet();
+    cvlr_assume!(MINIMUM_SLOTS_PER_EPOCH <= slots_per_epoch);
+    cvlr_assume!(slots_per_epoch <= DEFAULT_SLOTS_PER_EPOCH);
+    cvlr_assume!(leader_schedule_slot_offset <= slots_per_epoch);
+    EpochSchedule::custom(slots_per_epoch, leader_schedule_slot_offset, false)
+}
+
+/// True if the epochs of [clock] are the ones of its slot in [schedule]
+pub fn is_clock_consistent(clock: &Clock, schedule: &EpochSchedule) -> bool {
+    clock.epoch == schedule.get_epoch(clock.slot)
+        && clock.leader_schedule_epoch == schedule.get_leader_schedule_epoch(clock.slot)
+        && 0 <= clock.epoch_start_timestamp
+        && clock.epoch_start_timestamp <= clock.unix_timestamp
+}
+
+pub fn cvlr_nondet_clock(schedule: &EpochSchedule) -> Clock {
+    let clock = Clock {
+        slot: nondet(),
+        epoch_start_timestamp: nondet(),
+        epoch: nondet(),
+        leader_schedule_epoch: nondet(),
+        unix_timestamp: nondet(),
+    };
+    cvlr_assume!(is_clock_consistent(&clock, schedule));
+    clock
+}
+
+/// Clock at the same time as [prev] or later
+pub fn cvlr_nondet_clock_after(prev: &Clock, schedule: &EpochSchedule) -> Clock {
+    let clock = cvlr_nondet_clock(schedule);
+    cvlr_assume!(prev.slot <= clock.slot);
+    cvlr_assume!(prev.unix_timestamp <= clock.unix_timestamp);
+    if clock.epoch == prev.epoch {
+        cvlr_assume!(clock.epoch_start_timestamp == prev.epoch_start_timestamp);
+    } else {
+        cvlr_assume!(prev.unix_timestamp <= clock.epoch_start_timestamp);
+    }
+    clock
+}
+
+/// Rent with a nondet rate
+///
+/// The exemption threshold is the default of every cluster, since rules
+/// cannot reason about a nondet `f64`.
+pub fn cvlr_nondet_rent() -> Rent {
+    let rent = Rent {
+        lamports_per_byte_year: nondet(),
+        exemption_threshold: DEFAULT_EXEMPTION_THRESHOLD,
+        burn_percent: nondet(),
+    };
+    cvlr_assume!(rent.lamports_per_byte_year <= MAX_LAMPORTS_PER_BYTE_YEAR);
+    cvlr_assume!(rent.burn_percent <= 100);
+    rent
+}
+
+/// Integer version of `Rent::minimum_balance` for the default exemption
+/// threshold of two years
+///
+/// `Rent::minimum_balance` computes through `f64`, which the prover cannot
+/// reason about, so rules and the processors they check use this instead.
+pub fn minimum_balance(rent: &Rent, data_len: usize) -> u64 {
+    checked_minimum_balance(rent, data_len).unwrap()
+}
+
+/// [minimum_balance], or `None` if it overflows
+pub fn checked_minimum_balance(rent: &Rent, data_len: usize) -> Option<u64> {
+    (ACCOUNT_STORAGE_OVERHEAD + data_len as u64)
+        .checked_mul(rent.lamports_per_byte_year)?
+        .checked_mul(2)
+}
+
+/// Integer version of `Rent::is_exempt`, see [minimum_balance]
+pub fn is_rent_exempt(rent: &Rent, lamports: u64, data_len: usize) -> bool {
+    lamports >= minimum_balance(rent, data_len)
+}
@@ -0,0 +1,311 @@
+use crate::{
+    mul_div::mul_div_floor_u64, nonlinear::isqrt_bitwise_u128, transfer_fee::MAX_FEE_BASIS_POINTS,
+};
+use std::cmp;
+
+/// Constant-product pool of tokens A and B
+struct PoolState {
+    pub reserve_a: u64,
+    pub reserve_b: u64,
+    pub lp_supply: u64,
+    /// Swap fee in basis points, retained by the pool
+    pub fee_bps: u16,
+}
+
+impl PoolState {
+    /// Amount of the output token for [amount_in] of the input token
+    ///
+    /// Both the fee and the output are rounded down, in favour of the pool.
+    fn swap_out(&self, reserve_in: u64, reserve_out: u64, amount_in: u64) -> u64 {
+        let in_after_fee = mul_div_floor_u64(
+            amount_in,
+            (MAX_FEE_BASIS_POINTS - self.fee_bps) as u64,
+            MAX_FEE_BASIS_POINTS as u64,
+        )
+        .unwrap();
+        let reserve_in_post = reserve_in.checked_add(in_after_fee).unwrap();
+        mul_div_floor_u64(reserve_out, in_after_fee, reserve_in_post).unwrap()
+    }
+
+    pub fn swap_a_for_b(&mut self, amount_in: u64) -> u64 {
+        require_gt!(amount_in, 0);
+        let out = self.swap_out(self.reserve_a, self.reserve_b, amount_in);
+        self.reserve_a = self.reserve_a.checked_add(amount_in).unwrap();
+        self.reserve_b = self.reserve_b.checked_sub(out).unwrap();
+        out
+    }
+
+    pub fn swap_b_for_a(&mut self, amount_in: u64) -> u64 {
+        require_gt!(amount_in, 0);
+        let out = self.swap_out(self.reserve_b, self.reserve_a, amount_in);
+        self.reserve_b = self.reserve_b.checked_add(amount_in).unwrap();
+        self.reserve_a = self.reserve_a.checked_sub(out).unwrap();
+        out
+    }
+
+    pub fn add_liquidity(&mut self, amount_a: u64, amount_b: u64) -> u64 {
+        let lp = if self.lp_supply == 0 {
+            let k = (amount_a as u128) * (amount_b as u128);
+            isqrt_bitwise_u128(k).try_into().unwrap()
+        } else {
+            cmp::min(
+                mul_div_floor_u64(amount_a, self.lp_supply, self.reserve_a).unwrap(),
+                mul_div_floor_u64(amount_b, self.lp_supply, self.reserve_b).unwrap(),
+            )
+        };
+        require_gt!(lp, 0);
+
+        self.reserve_a = self.reserve_a.checked_add(amount_a).unwrap();
+        self.reserve_b = self.reserve_b.checked_add(amount_b).unwrap();
+        self.lp_supply = self.lp_supply.checked_add(lp).unwrap();
+        lp
+    }
+
+    pub fn remove_liquidity(&mut self, lp: u64) -> (u64, u64) {
+        let out_a = mul_div_floor_u64(lp, self.reserve_a, self.lp_supply).unwrap();
+        let out_b = mul_div_floor_u64(lp, self.reserve_b, self.lp_supply).unwrap();
+
+        self.lp_supply = self.lp_supply.checked_sub(lp).unwrap();
+        self.reserve_a = self.reserve_a.checked_sub(out_a).unwrap();
+        self.reserve_b = self.reserve_b.checked_sub(out_b).unwrap();
+        (out_a, out_b)
+    }
+}
+
+mod fv {
+    use super::*;
+    use cvlr::mathint::NativeInt as MathInt;
+    use cvlr::prelude::*;
+
+    struct FvPoolState {
+        reserve_a: MathInt,
+        reserve_b: MathInt,
+        lp_supply: MathInt,
+    }
+
+    impl FvPoolState {
+        pub fn new(pool: &PoolState) -> Self {
+            Self {
+                reserve_a: pool.reserve_a.into(),
+                reserve_b: pool.reserve_b.into(),
+                lp_supply: pool.lp_supply.into(),
+            }
+        }
+
+        pub fn k(&self) -> MathInt {
+            self.reserve_a * self.reserve_b
+        }
+
+        /// Every LP token is backed by reserves: `lp_supply^2 <= k`
+        pub fn assume_solvency(&self) {
+            let p = self;
+            cvlr_assume!(p.lp_supply * p.lp_supply <= p.k());
+        }
+
+        pub fn check_solvency(&self) {
+            let p = self;
+            cvlr_assert_le!(p.lp_supply * p.lp_supply, p.k());
+        }
+
+        /// A pool without LP tokens holds no reserves, as reserves only enter
+        /// through a deposit that mints LP tokens
+        pub fn assume_empty_has_no_reserves(&self) {
+            let p = self;
+            let zero = MathInt::from(0u64);
+            cvlr_assume!(p.lp_supply > zero || (p.reserve_a == zero && p.reserve_b == zero));
+        }
+
+        pub fn check_k_no_decrease(&self, old: &FvPoolState) {
+            let new = self;
+            cvlr_assert_ge!(new.k(), old.k());
+        }
+
+        /// Reserves per LP token do not decrease
+        pub fn check_no_dilution(&self, old: &FvPoolState) {
+            let new = self;
+            cvlr_assert_le!(old.reserve_a * new.lp_supply, new.reserve_a * old.lp_supply);
+            cvlr_assert_le!(old.reserve_b * new.lp_supply, new.reserve_b * old.lp_supply);
+        }
+    }
+
+    impl From<&PoolState> for FvPoolState {
+        fn from(pool: &PoolState) -> Self {
+            Self::new(pool)
+        }
+    }
+
+    impl cvlr::nondet::Nondet for PoolState {
+        fn nondet() -> Self {
+            let fee_bps = nondet();
+            cvlr_assume!(fee_bps <= MAX_FEE_BASIS_POINTS);
+            Self {
+                reserve_a: nondet(),
+                reserve_b: nondet(),
+                lp_supply: nondet(),
+                fee_bps,
+            }
+        }
+    }
+
+    impl cvlr::log::CvlrLog for FvPoolState {
+        #[inline(always)]
+        fn log(&self, tag: &str, logger: &mut cvlr::log::CvlrLogger) {
+            use cvlr::log::cvlr_log_with;
+            cvlr_log_with("", &tag, logger);
+            cvlr_log_with("\treserve_a", &self.reserve_a, logger);
+            cvlr_log_with("\treserve_b", &self.reserve_b, logger);
+            cvlr_log_with("\tlp_supply", &self.lp_supply, logger);
+        }
+    }
+
+    #[rule]
+    pub fn rule_amm_k_swap_a_for_b() {
+        let mut pool: PoolState = nondet();
+
+        let fv_pool_pre: FvPoolState = (&pool).into();
+
+        let amount_in: u64 = nondet();
+        let out = pool.swap_a_for_b(amount_in);
+        clog!(stringify!(pool.swap_a_for_b(amount_in)));
+        clog!(out);
+
+        let fv_pool_post: FvPoolState = (&pool).into();
+
+        clog!(fv_pool_pre, amount_in, fv_pool_post);
+
+        fv_pool_post.check_k_no_decrease(&fv_pool_pre);
+    }
+
+    #[rule]
+    pub fn rule_amm_k_swap_b_for_a() {
+        let mut pool: PoolState = nondet();
+
+        let fv_pool_pre: FvPoolState = (&pool).into();
+
+        let amount_in: u64 = nondet();
+        let out = pool.swap_b_for_a(amount_in);
+        clog!(stringify!(pool.swap_b_for_a(amount_in)));
+        clog!(out);
+
+        let fv_pool_post: FvPoolState = (&pool).into();
+
+        clog!(fv_pool_pre, amount_in, fv_pool_post);
+
+        fv_pool_post.check_k_no_decrease(&fv_pool_pre);
+    }
+
+    /// Output never exceeds the exact constant-product output for the input
+    /// after the exact fee
+    #[rule]
+    pub fn rule_amm_swap_rounds_for_pool() {
+        let mut pool: PoolState = nondet();
+
+        let fv_pool_pre: FvPoolState = (&pool).into();
+
+        let amount_in: u64 = nondet();
+        let out = pool.swap_a_for_b(amount_in);
+        clog!(fv_pool_pre, amount_in, pool.fee_bps, out);
+
+        // -- exact input after fee, scaled by MAX_FEE_BASIS_POINTS
+        let keep_bps = MathInt::from((MAX_FEE_BASIS_POINTS - pool.fee_bps) as u64);
+        let in_after_fee_scaled = MathInt::from(amount_in) * keep_bps;
+        cvlr_assert_le!(
+            MathInt::from(out)
+                * (fv_pool_pre.reserve_a * MAX_FEE_BASIS_POINTS as u64 + in_after_fee_scaled),
+            fv_pool_pre.reserve_b * in_after_fee_scaled
+        );
+    }
+
+    #[rule]
+    pub fn rule_amm_solvency_swap() {
+        let mut pool: PoolState = nondet();
+
+        let fv_pool_pre: FvPoolState = (&pool).into();
+        fv_pool_pre.assume_solvency();
+
+        let amount_in: u64 = nondet();
+        let _ = pool.swap_a_for_b(amount_in);
+        clog!(stringify!(pool.swap_a_for_b(amount_in);));
+
+        let fv_pool_post: FvPoolState = (&pool).into();
+
+        clog!(fv_pool_pre, amount_in, fv_pool_post);
+
+        fv_pool_post.check_solvency();
+    }
+
+    #[rule]
+    pub fn rule_amm_solvency_add_liquidity() {
+        let mut pool: PoolState = nondet();
+
+        let fv_pool_pre: FvPoolState = (&pool).into();
+        fv_pool_pre.assume_solvency();
+
+        let amount_a: u64 = nondet();
+        let amount_b: u64 = nondet();
+        let _ = pool.add_liquidity(amount_a, amount_b);
+        clog!(stringify!(pool.add_liquidity(amount_a, amount_b);));
+
+        let fv_pool_post: FvPoolState = (&pool).into();
+
+        clog!(fv_pool_pre, amount_a, amount_b, fv_pool_post);
+
+        fv_pool_post.check_solvency();
+    }
+
+    #[rule]
+    pub fn rule_amm_solvency_remove_liquidity() {
+        let mut pool: PoolState = nondet();
+
+        let fv_pool_pre: FvPoolState = (&pool).into();
+        fv_pool_pre.assume_solvency();
+
+        let lp_arg: u64 = nondet();
+        let _ = pool.remove_liquidity(lp_arg);
+        clog!(stringify!(pool.remove_liquidity(lp_arg);));
+
+        let fv_pool_post: FvPoolState = (&pool).into();
+
+        clog!(fv_pool_pre, lp_arg, fv_pool_post);
+
+        fv_pool_post.check_solvency();
+    }
+
+    #[rule]
+    pub fn rule_amm_no_dilution_add_liquidity() {
+        let mut pool: PoolState = nondet();
+
+        let fv_pool_pre: FvPoolState = (&pool).into();
+        fv_pool_pre.assume_empty_has_no_reserves();
+
+        let amount_a: u64 = nondet();
+        let amount_b: u64 = nondet();
+        let out = pool.add_liquidity(amount_a, amount_b);
+        clog!(stringify!(pool.add_liquidity(amount_a, amount_b)));
+        clog!(out);
+
+        let fv_pool_post: FvPoolState = (&pool).into();
+
+        clog!(fv_pool_pre, amount_a, amount_b, fv_pool_post);
+
+        fv_pool_post.check_no_dilution(&fv_pool_pre);
+    }
+
+    #[rule]
+    pub fn rule_amm_no_dilution_remove_liquidity() {
+        let mut pool: PoolState = nondet();
+
+        let fv_pool_pre: FvPoolState = (&pool).into();
+
+        let lp_arg: u64 = nondet();
+        let (out_a, out_b) = pool.remove_liquidity(lp_arg);
+        clog!(stringify!(pool.remove_liquidity(lp_arg)));
+        clog!(out_a, out_b);
+
+        let fv_pool_post: FvPoolState = (&pool).into();
+
+        clog!(fv_pool_pre, lp_arg, fv_pool_post);
+
+        fv_pool_post.check_no_dilution(&fv_pool_pre);
+    }
+}
@@ -0,0 +1,56 @@
+/// Declare a function contract and derive a mock and a rule from it
+///
+/// A contract is a pair of closures. `requires` takes references to the
+/// arguments, `ensures` takes references to the arguments and to the result.
+/// The macro generates
+///
+/// - a mock that asserts `requires` and returns a nondet result that satisfies
+///   `ensures`
+/// - a rule that checks that the `original` function satisfies `ensures` for
+///   all arguments that satisfy `requires`
+///
+/// Since the mock is derived from the same contract that is checked against
+/// the original, the mock cannot be stronger than the code it replaces.
+/// Arguments must be `Copy` and, together with the result, implement
+/// `CvlrLog` and `Nondet`.
+///
+/// ```ignore
+/// cvlr_contract! {
+///     pub fn compute_fee(amount: u64, fee_bps: u16) -> Option<u64>;
+///     original = crate::some_fee::compute_fee;
+///     requires = |_amount: &u64, fee_bps: &u16| *fee_bps <= 10_000;
+///     ensures = |amount: &u64, _fee_bps: &u16, fee: &Option<u64>| {
+///         matches!(fee, Some(f) if *f <= *amount)
+///     };
+///     rule = rule_compute_fee_contract;
+/// }
+/// ```
+#[macro_export]
+macro_rules! cvlr_contract {
+    (
+        $(#[$meta:meta])*
+        $vis:vis fn $mock:ident($( $arg:ident : $ty:ty ),* $(,)?) -> $ret:ty;
+        original = $orig:path;
+        requires = $requires:expr;
+        ensures = $ensures:expr;
+        rule = $rule:ident;
+    ) => {
+        $(#[$meta])*
+        #[inline(never)]
+        $vis fn $mock($( $arg: $ty ),*) -> $ret {
+            cvlr::cvlr_assert!(($requires)($( &$arg ),*));
+            cvlr::nondet::nondet_with(|ret: &$ret| ($ensures)($( &$arg, )* ret))
+        }
+
+        #[cvlr::rule]
+        pub fn $rule() {
+            $( let $arg: $ty = cvlr::nondet(); )*
+            cvlr::cvlr_assume!(($requires)($( &$arg ),*));
+            $( cvlr::clog!($arg); )*
+
+            let ret: $ret = $orig($( $arg ),*);
+            cvlr::clog!(ret);
+            cvlr::cvlr_assert!(($ensures)($( &$arg, )* &ret));
+        }
+    };
+}
@@ -1,12 +1,51 @@
 pub mod some_fee {
-    use cvlr::nondet::nondet_with;
-    /// Mock of compute_fee
-    ///
-    /// Returns a value that is less than amount, to correspond to some fee that is
-    /// <= 100%
-    #[inline(never)]
-    pub fn compute_fee(amount: u64, _fee_bps: u16) -> Option<u64> {
-        let fee = nondet_with(|x: &u64| 0 < *x && *x <= amount);
-        Some(fee)
+    crate::cvlr_contract! {
+        /// Mock of compute_fee
+        ///
+        /// Returns a value that is less than amount, to correspond to some fee that is
+        /// <= 100%
+        pub fn compute_fee(amount: u64, fee_bps: u16) -> Option<u64>;
+        original = crate::some_fee::compute_fee;
+        requires = |amount: &u64, fee_bps: &u16| {
+            *fee_bps <= 10_000 && amount.checked_mul(*fee_bps as u64).is_some()
+        };
+        ensures = |amount: &u64, _fee_bps: &u16, fee: &Option<u64>| {
+            matches!(fee, Some(f) if *f <= *amount)
+        };
+        rule = rule_compute_fee_contract;
+    }
+
+    crate::cvlr_contract! {
+        /// Mock of compute_fee that also promises a non-zero fee
+        ///
+        /// Not registered, and its rule is expected to be violated: the original
+        /// rounds small fees down to 0, so this mock is stronger than the code it
+        /// would replace.
+        pub fn compute_fee_positive(amount: u64, fee_bps: u16) -> Option<u64>;
+        original = crate::some_fee::compute_fee;
+        requires = |amount: &u64, fee_bps: &u16| {
+            *fee_bps <= 10_000 && amount.checked_mul(*fee_bps as u64).is_some()
+        };
+        ensures = |amount: &u64, _fee_bps: &u16, fee: &Option<u64>| {
+            matches!(fee, Some(f) if 0 < *f && *f <= *amount)
+        };
+        rule = rule_compute_fee_positive_contract;
+    }
+}
+
+crate::cvlr_register_mocks! {
+    setup_mock {
+        crate::some_fee::compute_fee => some_fee::compute_fee,
+    }
+    rules {
+        /// Calls the original compute_fee, which is replaced by its mock
+        pub fn rule_mocked_compute_fee() {
+            let amount: u64 = cvlr::nondet();
+            let fee_bps: u16 = cvlr::nondet::nondet_with(|x| *x <= 10_000);
+            cvlr::cvlr_assume!(amount.checked_mul(fee_bps as u64).is_some());
+            let fee = crate::some_fee::compute_fee(amount, fee_bps).unwrap();
+            cvlr::clog!(amount, fee_bps, fee);
+            cvlr::cvlr_assert_le!(fee, amount);
+        }
     }
 }
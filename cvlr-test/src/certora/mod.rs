@@ -1 +1,76 @@
-pub mod mocks;
\ No newline at end of file
+mod contract;
+pub mod mocks;
+
+mod rt_decls {
+    extern "C" {
+        pub fn CVT_register_mock_fn(fn_orig: usize, fn_mock: usize);
+    }
+}
+
+/// Run-time implementation of mock registration
+///
+/// Mocks are never registered at run-time, so the original function is
+/// always called.
+#[cfg(feature = "rt")]
+mod rt_impls {
+    #[no_mangle]
+    pub extern "C" fn CVT_register_mock_fn(_fn_orig: usize, _fn_mock: usize) {}
+}
+
+/// Replace every call to [fn_orig] by a call to [fn_mock]
+///
+/// Prefer [cvlr_register_mocks] that checks that both functions have the
+/// same signature.
+#[inline(always)]
+pub fn cvlr_register_mock_fn(fn_orig: usize, fn_mock: usize) {
+    unsafe {
+        rt_decls::CVT_register_mock_fn(fn_orig, fn_mock);
+    }
+}
+
+/// Declare a setup function that registers mocks, and rules that run with
+/// them
+///
+/// Each entry pairs an original function with its mock. Both must have the
+/// same signature, otherwise the setup function does not compile.
+///
+/// Rules declared in the optional `rules` block opt into the mocks: each of
+/// them calls the setup function on entry, so every call to an original
+/// function in the rule goes to its mock. Rules declared elsewhere verify the
+/// original functions. Under `rt` registration does nothing, and the originals
+/// are called.
+///
+/// ```ignore
+/// cvlr_register_mocks! {
+///     setup_mock {
+///         crate::some_fee::compute_fee => crate::certora::mocks::some_fee::compute_fee,
+///     }
+///     rules {
+///         pub fn rule_mocked_compute_fee() { ... }
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! cvlr_register_mocks {
+    (
+        $setup:ident { $( $orig:path => $mock:path ),* $(,)? }
+        $( rules { $( $(#[$meta:meta])* $vis:vis fn $rule:ident() $body:block )* } )?
+    ) => {
+        #[no_mangle]
+        pub fn $setup() {
+            $(
+                // -- both functions coerce to a common fn pointer type only
+                // -- if their signatures match
+                let [fn_orig, fn_mock] = [$orig, $mock];
+                $crate::certora::cvlr_register_mock_fn(fn_orig as usize, fn_mock as usize);
+            )*
+        }
+
+        $($(
+            $(#[$meta])*
+            #[cvlr::rule]
+            #[cvlr::cvlr_hook_on_entry($setup())]
+            $vis fn $rule() $body
+        )*)?
+    };
+}
@@ -0,0 +1,247 @@
+//! Scaling of token amounts between mints with different decimals
+//!
+//! Scaling up multiplies by a power of ten and fails with `None` exactly when
+//! the result does not fit. Scaling down divides by a power of ten with an
+//! explicit rounding direction and cannot fail.
+
+use cvlr::mathint::NativeInt as MathInt;
+use cvlr::prelude::*;
+
+/// Largest number of decimals used by rules
+///
+/// `10^38` is the largest power of ten that fits into u128, so rules cover
+/// every case where a power of ten is representable.
+const MAX_RULE_DECIMALS: u8 = 38;
+
+macro_rules! impl_decimals {
+    ($uint:ty,
+     $pow10:ident,
+     $scale_up:ident,
+     $scale_down_floor:ident,
+     $scale_down_ceil:ident,
+     $convert_floor:ident,
+     $convert_ceil:ident) => {
+        fn $pow10(decimals: u8) -> Option<$uint> {
+            <$uint>::checked_pow(10, decimals as u32)
+        }
+
+        /// `amount * 10^decimals`, `None` if the result does not fit
+        pub fn $scale_up(amount: $uint, decimals: u8) -> Option<$uint> {
+            if amount == 0 {
+                return Some(0);
+            }
+            amount.checked_mul($pow10(decimals)?)
+        }
+
+        /// `amount / 10^decimals` rounded down
+        pub fn $scale_down_floor(amount: $uint, decimals: u8) -> $uint {
+            match $pow10(decimals) {
+                Some(p) => amount / p,
+                // -- amount is less than 10^decimals
+                None => 0,
+            }
+        }
+
+        /// `amount / 10^decimals` rounded up
+        pub fn $scale_down_ceil(amount: $uint, decimals: u8) -> $uint {
+            match $pow10(decimals) {
+                Some(p) => amount.div_ceil(p),
+                // -- amount is less than 10^decimals
+                None => (amount > 0) as $uint,
+            }
+        }
+
+        /// Convert [amount] from [from] to [to] decimals, rounded down
+        pub fn $convert_floor(amount: $uint, from: u8, to: u8) -> Option<$uint> {
+            if to >= from {
+                $scale_up(amount, to - from)
+            } else {
+                Some($scale_down_floor(amount, from - to))
+            }
+        }
+
+        /// Convert [amount] from [from] to [to] decimals, rounded up
+        pub fn $convert_ceil(amount: $uint, from: u8, to: u8) -> Option<$uint> {
+            if to >= from {
+                $scale_up(amount, to - from)
+            } else {
+                Some($scale_down_ceil(amount, from - to))
+            }
+        }
+    };
+}
+
+impl_decimals!(
+    u64,
+    pow10_u64,
+    scale_up_u64,
+    scale_down_floor_u64,
+    scale_down_ceil_u64,
+    convert_decimals_floor_u64,
+    convert_decimals_ceil_u64
+);
+
+impl_decimals!(
+    u128,
+    pow10_u128,
+    scale_up_u128,
+    scale_down_floor_u128,
+    scale_down_ceil_u128,
+    convert_decimals_floor_u128,
+    convert_decimals_ceil_u128
+);
+
+/// `10^decimals` as a mathematical integer
+fn math_pow10(decimals: u8) -> MathInt {
+    let mut p = MathInt::from(1u64);
+    for _ in 0..decimals {
+        p = p * 10;
+    }
+    p
+}
+
+macro_rules! impl_decimals_rules {
+    ($uint:ty, $is_uint:ident,
+     $scale_up:ident,
+     $scale_down_floor:ident,
+     $scale_down_ceil:ident,
+     $convert_floor:ident,
+     $convert_ceil:ident,
+     $rule_up_down:ident,
+     $rule_down_up:ident,
+     $rule_up_overflow:ident,
+     $rule_down_rounding:ident,
+     $rule_convert_roundtrip:ident) => {
+        /// Scaling up and then down is the identity
+        #[rule]
+        pub fn $rule_up_down() {
+            let amount: $uint = nondet();
+            let decimals: u8 = nondet();
+            cvlr_assume!(decimals <= MAX_RULE_DECIMALS);
+            clog!(amount, decimals);
+
+            let up = $scale_up(amount, decimals);
+            clog!(up);
+            if let Some(up) = up {
+                cvlr_assert_eq!($scale_down_floor(up, decimals), amount);
+                cvlr_assert_eq!($scale_down_ceil(up, decimals), amount);
+            }
+        }
+
+        /// Scaling down and then up never exceeds the original, or never goes
+        /// below it when rounding up
+        #[rule]
+        pub fn $rule_down_up() {
+            let amount: $uint = nondet();
+            let decimals: u8 = nondet();
+            cvlr_assume!(decimals <= MAX_RULE_DECIMALS);
+            clog!(amount, decimals);
+
+            let floor = $scale_down_floor(amount, decimals);
+            let ceil = $scale_down_ceil(amount, decimals);
+            clog!(floor, ceil);
+
+            let floor_up = $scale_up(floor, decimals);
+            // -- cannot overflow, it is at most the original
+            cvlr_assert!(floor_up.is_some());
+            cvlr_assert_le!(floor_up.unwrap(), amount);
+
+            if let Some(ceil_up) = $scale_up(ceil, decimals) {
+                cvlr_assert_ge!(ceil_up, amount);
+            }
+        }
+
+        /// Scaling up fails exactly when the result does not fit
+        #[rule]
+        pub fn $rule_up_overflow() {
+            let amount: $uint = nondet();
+            let decimals: u8 = nondet();
+            cvlr_assume!(decimals <= MAX_RULE_DECIMALS);
+            clog!(amount, decimals);
+
+            let expected = MathInt::from(amount) * math_pow10(decimals);
+            match $scale_up(amount, decimals) {
+                Some(v) => cvlr_assert_eq!(MathInt::from(v), expected),
+                None => cvlr_assert!(!expected.$is_uint()),
+            }
+        }
+
+        #[rule]
+        pub fn $rule_down_rounding() {
+            let amount: $uint = nondet();
+            let decimals: u8 = nondet();
+            cvlr_assume!(decimals <= MAX_RULE_DECIMALS);
+            clog!(amount, decimals);
+
+            let p = math_pow10(decimals);
+            let a = MathInt::from(amount);
+            let floor = MathInt::from($scale_down_floor(amount, decimals));
+            let ceil = MathInt::from($scale_down_ceil(amount, decimals));
+            clog!(floor, ceil);
+
+            cvlr_assert_le!(floor * p, a);
+            cvlr_assert_lt!(a, (floor + 1) * p);
+            cvlr_assert_ge!(ceil * p, a);
+            cvlr_assert_gt!(a + p, ceil * p);
+        }
+
+        /// Converting to other decimals and back never gains, and is lossless
+        /// when converting to more decimals
+        #[rule]
+        pub fn $rule_convert_roundtrip() {
+            let amount: $uint = nondet();
+            let from: u8 = nondet();
+            let to: u8 = nondet();
+            cvlr_assume!(from <= MAX_RULE_DECIMALS);
+            cvlr_assume!(to <= MAX_RULE_DECIMALS);
+            clog!(amount, from, to);
+
+            if let Some(there) = $convert_floor(amount, from, to) {
+                let back = $convert_floor(there, to, from);
+                clog!(there, back);
+                cvlr_assert!(back.is_some());
+                cvlr_assert_le!(back.unwrap(), amount);
+                if to >= from {
+                    cvlr_assert_eq!(back.unwrap(), amount);
+                }
+            }
+
+            if let Some(there) = $convert_ceil(amount, from, to) {
+                if let Some(back) = $convert_ceil(there, to, from) {
+                    clog!(there, back);
+                    cvlr_assert_ge!(back, amount);
+                }
+            }
+        }
+    };
+}
+
+impl_decimals_rules!(
+    u64,
+    is_u64,
+    scale_up_u64,
+    scale_down_floor_u64,
+    scale_down_ceil_u64,
+    convert_decimals_floor_u64,
+    convert_decimals_ceil_u64,
+    rule_decimals_up_down_u64,
+    rule_decimals_down_up_u64,
+    rule_decimals_up_overflow_u64,
+    rule_decimals_down_rounding_u64,
+    rule_decimals_convert_roundtrip_u64
+);
+
+impl_decimals_rules!(
+    u128,
+    is_u128,
+    scale_up_u128,
+    scale_down_floor_u128,
+    scale_down_ceil_u128,
+    convert_decimals_floor_u128,
+    convert_decimals_ceil_u128,
+    rule_decimals_up_down_u128,
+    rule_decimals_down_up_u128,
+    rule_decimals_up_overflow_u128,
+    rule_decimals_down_rounding_u128,
+    rule_decimals_convert_roundtrip_u128
+);
@@ -1,15 +1,11 @@
 /// Example of a specification
+use crate::mul_div::mul_div_ceil_u64;
 use cvlr::prelude::*;
 
 /// Function under verification
 fn compute_fee(amount: u64, fee_bps: u16) -> Result<u64,()> {
     if amount > 0 {
-        Ok((amount as u128)
-            .checked_mul(fee_bps as u128)
-            .and_then(|x| Some(x.div_ceil(10_000)))
-            .ok_or(())?
-            .try_into()
-            .map_err(|_| ())?)
+        mul_div_ceil_u64(amount, fee_bps as u64, 10_000).ok_or(())
     } else {
         Err(())
     }
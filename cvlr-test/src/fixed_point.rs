@@ -0,0 +1,259 @@
+//! Properties of fixed-point numbers, generic over the number of fraction bits
+//!
+//! Every property is a generic function over `F`. Rules instantiate the
+//! properties for common precisions. `NativeFixedU128` keeps `2^F` in a u64, so
+//! `F` must be below 64.
+//!
+//! Arithmetic properties hold for every `F`, since `NativeFixedU128` assumes
+//! that results fit. Whether a precision suits price math is decided by the
+//! price rules: a price in `[1 / PRICE_SCALE, PRICE_SCALE]` must be
+//! representable, and its product with any token amount must fit. Of the
+//! instantiated precisions only 32 satisfies both, 16 is too coarse and 48 and
+//! 60 overflow.
+
+use cvlr::mathint::NativeInt as MathInt;
+use cvlr::prelude::*;
+use cvlr_fixed::native_fixed::NativeFixedU128;
+
+/// Largest price, and inverse of the smallest price, in quote units per token
+const PRICE_SCALE: u64 = 1_000_000_000;
+
+/// `2^F` as a mathematical integer
+fn base<const F: u32>() -> MathInt {
+    MathInt::from(1u64 << F)
+}
+
+/// Underlying bits of [x] as a mathematical integer
+fn bits<const F: u32>(x: NativeFixedU128<F>) -> MathInt {
+    MathInt::from(x.to_bits())
+}
+
+/// `to_floor` and `to_ceil` enclose the value and differ by at most 1
+fn check_floor_ceil<const F: u32>() {
+    let x: NativeFixedU128<F> = nondet();
+    clog!(x);
+
+    let floor = x.to_floor();
+    let ceil = x.to_ceil();
+    clog!(floor, ceil);
+
+    cvlr_assert_le!(floor, ceil);
+    cvlr_assert_le!(ceil, floor + 1);
+    cvlr_assert_le!(floor * base::<F>(), bits(x));
+    cvlr_assert_lt!(bits(x), (floor + 1) * base::<F>());
+    cvlr_assert_ge!(ceil * base::<F>(), bits(x));
+    if floor * base::<F>() == bits(x) {
+        cvlr_assert_eq!(floor, ceil);
+    }
+    cvlr_satisfy!(floor != ceil);
+}
+
+/// Integers are represented exactly
+fn check_from_int<const F: u32>() {
+    let n: u64 = nondet();
+    let x = NativeFixedU128::<F>::from(n);
+    clog!(n, x);
+
+    cvlr_assert_eq!(x.to_floor(), MathInt::from(n));
+    cvlr_assert_eq!(x.to_ceil(), MathInt::from(n));
+    cvlr_satisfy!(n == u64::MAX);
+}
+
+/// `from_bits` and `to_bits` are inverse of each other
+fn check_bits_roundtrip<const F: u32>() {
+    let b: u128 = nondet();
+    let x = NativeFixedU128::<F>::from_bits(b);
+    clog!(b, x);
+    cvlr_assert_eq!(x.to_bits(), b);
+
+    let y: NativeFixedU128<F> = nondet();
+    cvlr_assert_eq!(NativeFixedU128::<F>::from_bits(y.to_bits()), y);
+    cvlr_satisfy!(b > u64::MAX as u128);
+}
+
+/// Multiplication rounds down by less than one unit in the last place
+fn check_mul_rounding<const F: u32>() {
+    let x: NativeFixedU128<F> = nondet();
+    let y: NativeFixedU128<F> = nondet();
+    let z = x * y;
+    clog!(x, y, z);
+
+    let exact = bits(x) * bits(y);
+    cvlr_assert_le!(bits(z) * base::<F>(), exact);
+    cvlr_assert_lt!(exact, (bits(z) + 1) * base::<F>());
+    cvlr_satisfy!(bits(z) * base::<F>() < exact);
+}
+
+/// Multiplication is monotone in each argument
+fn check_mul_monotone<const F: u32>() {
+    let x1: NativeFixedU128<F> = nondet();
+    let x2: NativeFixedU128<F> = nondet();
+    let y: NativeFixedU128<F> = nondet();
+    cvlr_assume!(x1 <= x2);
+    clog!(x1, x2, y);
+
+    cvlr_assert_le!(x1 * y, x2 * y);
+    cvlr_assert_le!(y * x1, y * x2);
+    cvlr_satisfy!(x1 * y < x2 * y);
+}
+
+/// Division rounds down by less than one unit in the last place
+fn check_div_rounding<const F: u32>() {
+    let x: NativeFixedU128<F> = nondet();
+    let y: NativeFixedU128<F> = nondet();
+    cvlr_assume!(y.to_bits() > 0);
+    let z = x / y;
+    clog!(x, y, z);
+
+    let exact = bits(x) * base::<F>();
+    cvlr_assert_le!(bits(z) * bits(y), exact);
+    cvlr_assert_lt!(exact, (bits(z) + 1) * bits(y));
+    cvlr_satisfy!(bits(z) * bits(y) < exact);
+}
+
+/// Division is increasing in the numerator and decreasing in the denominator
+fn check_div_monotone<const F: u32>() {
+    let x1: NativeFixedU128<F> = nondet();
+    let x2: NativeFixedU128<F> = nondet();
+    let y1: NativeFixedU128<F> = nondet();
+    let y2: NativeFixedU128<F> = nondet();
+    cvlr_assume!(x1 <= x2);
+    cvlr_assume!(y1.to_bits() > 0);
+    cvlr_assume!(y1 <= y2);
+    clog!(x1, x2, y1, y2);
+
+    cvlr_assert_le!(x1 / y1, x2 / y1);
+    cvlr_assert_ge!(x1 / y1, x1 / y2);
+    cvlr_satisfy!(x1 / y1 > x1 / y2);
+}
+
+/// The product of the largest price and any token amount fits
+fn check_price_capacity<const F: u32>() {
+    let price: u64 = nondet();
+    let amount: u64 = nondet();
+    cvlr_assume!(price <= PRICE_SCALE);
+    clog!(price, amount);
+
+    let exact = MathInt::from(price) * MathInt::from(amount) * base::<F>();
+    cvlr_assert_le!(exact, MathInt::from(u128::MAX));
+    cvlr_satisfy!(price == PRICE_SCALE && amount == u64::MAX);
+}
+
+/// The smallest price is not rounded to zero
+fn check_price_resolution<const F: u32>() {
+    let min_price = NativeFixedU128::<F>::from_bits((1u128 << F) / PRICE_SCALE as u128);
+    clog!(min_price);
+
+    cvlr_assert!(min_price.to_bits() > 0);
+}
+
+macro_rules! impl_fixed_point_rules {
+    ($f:literal,
+     $floor_ceil:ident,
+     $from_int:ident,
+     $bits_roundtrip:ident,
+     $mul_rounding:ident,
+     $mul_monotone:ident,
+     $div_rounding:ident,
+     $div_monotone:ident,
+     $price_capacity:ident,
+     $price_resolution:ident) => {
+        #[rule]
+        pub fn $floor_ceil() {
+            check_floor_ceil::<$f>();
+        }
+
+        #[rule]
+        pub fn $from_int() {
+            check_from_int::<$f>();
+        }
+
+        #[rule]
+        pub fn $bits_roundtrip() {
+            check_bits_roundtrip::<$f>();
+        }
+
+        #[rule]
+        pub fn $mul_rounding() {
+            check_mul_rounding::<$f>();
+        }
+
+        #[rule]
+        pub fn $mul_monotone() {
+            check_mul_monotone::<$f>();
+        }
+
+        #[rule]
+        pub fn $div_rounding() {
+            check_div_rounding::<$f>();
+        }
+
+        #[rule]
+        pub fn $div_monotone() {
+            check_div_monotone::<$f>();
+        }
+
+        #[rule]
+        pub fn $price_capacity() {
+            check_price_capacity::<$f>();
+        }
+
+        #[rule]
+        pub fn $price_resolution() {
+            check_price_resolution::<$f>();
+        }
+    };
+}
+
+impl_fixed_point_rules!(
+    16,
+    rule_fixed_floor_ceil_f16,
+    rule_fixed_from_int_f16,
+    rule_fixed_bits_roundtrip_f16,
+    rule_fixed_mul_rounding_f16,
+    rule_fixed_mul_monotone_f16,
+    rule_fixed_div_rounding_f16,
+    rule_fixed_div_monotone_f16,
+    rule_fixed_price_capacity_f16,
+    rule_fixed_price_resolution_f16
+);
+
+impl_fixed_point_rules!(
+    32,
+    rule_fixed_floor_ceil_f32,
+    rule_fixed_from_int_f32,
+    rule_fixed_bits_roundtrip_f32,
+    rule_fixed_mul_rounding_f32,
+    rule_fixed_mul_monotone_f32,
+    rule_fixed_div_rounding_f32,
+    rule_fixed_div_monotone_f32,
+    rule_fixed_price_capacity_f32,
+    rule_fixed_price_resolution_f32
+);
+
+impl_fixed_point_rules!(
+    48,
+    rule_fixed_floor_ceil_f48,
+    rule_fixed_from_int_f48,
+    rule_fixed_bits_roundtrip_f48,
+    rule_fixed_mul_rounding_f48,
+    rule_fixed_mul_monotone_f48,
+    rule_fixed_div_rounding_f48,
+    rule_fixed_div_monotone_f48,
+    rule_fixed_price_capacity_f48,
+    rule_fixed_price_resolution_f48
+);
+
+// -- the precision used by `arithmetic::mul_div_ceil_of_frac`
+impl_fixed_point_rules!(
+    60,
+    rule_fixed_floor_ceil_f60,
+    rule_fixed_from_int_f60,
+    rule_fixed_bits_roundtrip_f60,
+    rule_fixed_mul_rounding_f60,
+    rule_fixed_mul_monotone_f60,
+    rule_fixed_div_rounding_f60,
+    rule_fixed_div_monotone_f60,
+    rule_fixed_price_capacity_f60,
+    rule_fixed_price_resolution_f60
+);
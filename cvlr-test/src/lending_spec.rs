@@ -0,0 +1,385 @@
+use crate::mul_div::{mul_div_ceil_u128, mul_div_floor_u128};
+
+/// Value of the borrow index that stands for 1
+const INDEX_ONE: u128 = 1_000_000_000_000;
+
+/// Lending reserve of a single token
+///
+/// Deposits are nominal, interest on borrows is accrued through a cumulative
+/// borrow index, and debt is stored scaled down by the index.
+struct LendingReserve {
+    /// Tokens held by the reserve
+    pub cash: u64,
+    /// Tokens owed to depositors
+    pub total_deposits: u64,
+    /// Sum of the scaled debt of all positions
+    pub total_scaled_debt: u64,
+    /// Cumulative borrow index, in units of [INDEX_ONE]
+    pub borrow_index: u128,
+    /// Interest per slot, in units of [INDEX_ONE]
+    pub rate_per_slot: u64,
+    pub last_update_slot: u64,
+}
+
+/// Debt of a single borrower
+struct BorrowPosition {
+    pub scaled_debt: u64,
+}
+
+impl LendingReserve {
+    /// Debt for [scaled] units of scaled debt, rounded up
+    fn debt_of(&self, scaled: u64) -> u64 {
+        mul_div_ceil_u128(scaled as u128, self.borrow_index, INDEX_ONE)
+            .unwrap()
+            .try_into()
+            .unwrap()
+    }
+
+    pub fn total_debt(&self) -> u64 {
+        self.debt_of(self.total_scaled_debt)
+    }
+
+    pub fn position_debt(&self, position: &BorrowPosition) -> u64 {
+        self.debt_of(position.scaled_debt)
+    }
+
+    /// Accrue interest from the last update up to [slot]
+    ///
+    /// The index grows by `rate_per_slot` for every elapsed slot, rounded up.
+    pub fn accrue(&mut self, slot: u64) {
+        let elapsed = slot.checked_sub(self.last_update_slot).unwrap();
+        let rate = (self.rate_per_slot as u128)
+            .checked_mul(elapsed as u128)
+            .unwrap();
+        let interest = mul_div_ceil_u128(self.borrow_index, rate, INDEX_ONE).unwrap();
+        self.borrow_index = self.borrow_index.checked_add(interest).unwrap();
+        self.last_update_slot = slot;
+    }
+
+    pub fn deposit(&mut self, amount: u64) {
+        require_gt!(amount, 0);
+        self.cash = self.cash.checked_add(amount).unwrap();
+        self.total_deposits = self.total_deposits.checked_add(amount).unwrap();
+    }
+
+    pub fn withdraw(&mut self, amount: u64) {
+        self.cash = self.cash.checked_sub(amount).unwrap();
+        self.total_deposits = self.total_deposits.checked_sub(amount).unwrap();
+    }
+
+    /// Borrow [amount] tokens, scaled debt is rounded up against the borrower
+    pub fn borrow(&mut self, position: &mut BorrowPosition, amount: u64) {
+        require_gt!(amount, 0);
+        let scaled: u64 = mul_div_ceil_u128(amount as u128, INDEX_ONE, self.borrow_index)
+            .unwrap()
+            .try_into()
+            .unwrap();
+
+        self.cash = self.cash.checked_sub(amount).unwrap();
+        self.total_scaled_debt = self.total_scaled_debt.checked_add(scaled).unwrap();
+        position.scaled_debt = position.scaled_debt.checked_add(scaled).unwrap();
+    }
+
+    /// Repay [amount] tokens, scaled debt is rounded down against the borrower
+    pub fn repay(&mut self, position: &mut BorrowPosition, amount: u64) {
+        let scaled: u64 = mul_div_floor_u128(amount as u128, INDEX_ONE, self.borrow_index)
+            .unwrap()
+            .try_into()
+            .unwrap();
+        require_le!(scaled, position.scaled_debt);
+
+        self.cash = self.cash.checked_add(amount).unwrap();
+        self.total_scaled_debt = self.total_scaled_debt.checked_sub(scaled).unwrap();
+        position.scaled_debt -= scaled;
+    }
+
+    /// Repay the whole debt of [position], returns the amount paid
+    pub fn repay_all(&mut self, position: &mut BorrowPosition) -> u64 {
+        let amount = self.position_debt(position);
+
+        self.cash = self.cash.checked_add(amount).unwrap();
+        self.total_scaled_debt = self
+            .total_scaled_debt
+            .checked_sub(position.scaled_debt)
+            .unwrap();
+        position.scaled_debt = 0;
+        amount
+    }
+}
+
+mod fv {
+    use super::*;
+    use cvlr::mathint::NativeInt as MathInt;
+    use cvlr::prelude::*;
+
+    struct FvLendingReserve {
+        cash: MathInt,
+        total_deposits: MathInt,
+        total_debt: MathInt,
+    }
+
+    impl FvLendingReserve {
+        pub fn new(reserve: &LendingReserve) -> Self {
+            Self {
+                cash: reserve.cash.into(),
+                total_deposits: reserve.total_deposits.into(),
+                total_debt: reserve.total_debt().into(),
+            }
+        }
+
+        pub fn assume_solvency(&self) {
+            let r = self;
+            cvlr_assume!(r.total_deposits <= r.cash + r.total_debt);
+        }
+
+        pub fn check_solvency(&self) {
+            let r = self;
+            cvlr_assert_le!(r.total_deposits, r.cash + r.total_debt);
+        }
+    }
+
+    impl From<&LendingReserve> for FvLendingReserve {
+        fn from(reserve: &LendingReserve) -> Self {
+            Self::new(reserve)
+        }
+    }
+
+    impl cvlr::nondet::Nondet for LendingReserve {
+        fn nondet() -> Self {
+            let borrow_index = nondet();
+            cvlr_assume!(borrow_index >= INDEX_ONE);
+            Self {
+                cash: nondet(),
+                total_deposits: nondet(),
+                total_scaled_debt: nondet(),
+                borrow_index,
+                rate_per_slot: nondet(),
+                last_update_slot: nondet(),
+            }
+        }
+    }
+
+    impl cvlr::nondet::Nondet for BorrowPosition {
+        fn nondet() -> Self {
+            Self {
+                scaled_debt: nondet(),
+            }
+        }
+    }
+
+    impl cvlr::log::CvlrLog for FvLendingReserve {
+        #[inline(always)]
+        fn log(&self, tag: &str, logger: &mut cvlr::log::CvlrLogger) {
+            use cvlr::log::cvlr_log_with;
+            cvlr_log_with("", &tag, logger);
+            cvlr_log_with("\tcash", &self.cash, logger);
+            cvlr_log_with("\ttotal_deposits", &self.total_deposits, logger);
+            cvlr_log_with("\ttotal_debt", &self.total_debt, logger);
+        }
+    }
+
+    /// Nondeterministic position that is part of [reserve]
+    fn nondet_position(reserve: &LendingReserve) -> BorrowPosition {
+        let position: BorrowPosition = nondet();
+        cvlr_assume!(position.scaled_debt <= reserve.total_scaled_debt);
+        position
+    }
+
+    #[rule]
+    pub fn rule_lending_solvency_deposit() {
+        let mut reserve: LendingReserve = nondet();
+
+        let fv_reserve_pre: FvLendingReserve = (&reserve).into();
+        fv_reserve_pre.assume_solvency();
+
+        let amount: u64 = nondet();
+        reserve.deposit(amount);
+        clog!(stringify!(reserve.deposit(amount);));
+
+        let fv_reserve_post: FvLendingReserve = (&reserve).into();
+
+        clog!(fv_reserve_pre, amount, fv_reserve_post);
+
+        fv_reserve_post.check_solvency();
+    }
+
+    #[rule]
+    pub fn rule_lending_solvency_withdraw() {
+        let mut reserve: LendingReserve = nondet();
+
+        let fv_reserve_pre: FvLendingReserve = (&reserve).into();
+        fv_reserve_pre.assume_solvency();
+
+        let amount: u64 = nondet();
+        reserve.withdraw(amount);
+        clog!(stringify!(reserve.withdraw(amount);));
+
+        let fv_reserve_post: FvLendingReserve = (&reserve).into();
+
+        clog!(fv_reserve_pre, amount, fv_reserve_post);
+
+        fv_reserve_post.check_solvency();
+    }
+
+    #[rule]
+    pub fn rule_lending_solvency_borrow() {
+        let mut reserve: LendingReserve = nondet();
+        let mut position = nondet_position(&reserve);
+
+        let fv_reserve_pre: FvLendingReserve = (&reserve).into();
+        fv_reserve_pre.assume_solvency();
+
+        let amount: u64 = nondet();
+        reserve.borrow(&mut position, amount);
+        clog!(stringify!(reserve.borrow(&mut position, amount);));
+
+        let fv_reserve_post: FvLendingReserve = (&reserve).into();
+
+        clog!(fv_reserve_pre, amount, fv_reserve_post);
+
+        fv_reserve_post.check_solvency();
+    }
+
+    #[rule]
+    pub fn rule_lending_solvency_repay() {
+        let mut reserve: LendingReserve = nondet();
+        let mut position = nondet_position(&reserve);
+
+        let fv_reserve_pre: FvLendingReserve = (&reserve).into();
+        fv_reserve_pre.assume_solvency();
+
+        let amount: u64 = nondet();
+        reserve.repay(&mut position, amount);
+        clog!(stringify!(reserve.repay(&mut position, amount);));
+
+        let fv_reserve_post: FvLendingReserve = (&reserve).into();
+
+        clog!(fv_reserve_pre, amount, fv_reserve_post);
+
+        fv_reserve_post.check_solvency();
+    }
+
+    #[rule]
+    pub fn rule_lending_solvency_repay_all() {
+        let mut reserve: LendingReserve = nondet();
+        let mut position = nondet_position(&reserve);
+
+        let fv_reserve_pre: FvLendingReserve = (&reserve).into();
+        fv_reserve_pre.assume_solvency();
+
+        let amount = reserve.repay_all(&mut position);
+        clog!(stringify!(reserve.repay_all(&mut position);));
+
+        let fv_reserve_post: FvLendingReserve = (&reserve).into();
+
+        clog!(fv_reserve_pre, amount, fv_reserve_post);
+
+        fv_reserve_post.check_solvency();
+    }
+
+    #[rule]
+    pub fn rule_lending_solvency_accrue() {
+        let mut reserve: LendingReserve = nondet();
+
+        let fv_reserve_pre: FvLendingReserve = (&reserve).into();
+        fv_reserve_pre.assume_solvency();
+
+        let slot: u64 = nondet();
+        reserve.accrue(slot);
+        clog!(stringify!(reserve.accrue(slot);));
+
+        let fv_reserve_post: FvLendingReserve = (&reserve).into();
+
+        clog!(fv_reserve_pre, slot, fv_reserve_post);
+
+        fv_reserve_post.check_solvency();
+    }
+
+    /// Debt grows by at least the borrowed amount, for the position and in total
+    #[rule]
+    pub fn rule_lending_debt_not_undercounted_borrow() {
+        let mut reserve: LendingReserve = nondet();
+        let mut position = nondet_position(&reserve);
+
+        let total_pre = MathInt::from(reserve.total_debt());
+        let position_pre = MathInt::from(reserve.position_debt(&position));
+
+        let amount: u64 = nondet();
+        reserve.borrow(&mut position, amount);
+        clog!(stringify!(reserve.borrow(&mut position, amount)));
+
+        let total_post = MathInt::from(reserve.total_debt());
+        let position_post = MathInt::from(reserve.position_debt(&position));
+
+        clog!(total_pre, position_pre, amount, total_post, position_post);
+
+        cvlr_assert_ge!(total_post, total_pre + amount);
+        cvlr_assert_ge!(position_post, position_pre + amount);
+    }
+
+    /// Debt decreases by at most the repaid amount
+    #[rule]
+    pub fn rule_lending_debt_not_undercounted_repay() {
+        let mut reserve: LendingReserve = nondet();
+        let mut position = nondet_position(&reserve);
+
+        let total_pre = MathInt::from(reserve.total_debt());
+        let position_pre = MathInt::from(reserve.position_debt(&position));
+
+        let amount: u64 = nondet();
+        reserve.repay(&mut position, amount);
+        clog!(stringify!(reserve.repay(&mut position, amount)));
+
+        let total_post = MathInt::from(reserve.total_debt());
+        let position_post = MathInt::from(reserve.position_debt(&position));
+
+        clog!(total_pre, position_pre, amount, total_post, position_post);
+
+        cvlr_assert_ge!(total_post + amount, total_pre);
+        cvlr_assert_ge!(position_post + amount, position_pre);
+    }
+
+    /// Accrual never decreases debt, and the index never decreases
+    #[rule]
+    pub fn rule_lending_debt_not_undercounted_accrue() {
+        let mut reserve: LendingReserve = nondet();
+        let position = nondet_position(&reserve);
+
+        let index_pre = reserve.borrow_index;
+        let total_pre = reserve.total_debt();
+        let position_pre = reserve.position_debt(&position);
+
+        let slot: u64 = nondet();
+        reserve.accrue(slot);
+        clog!(stringify!(reserve.accrue(slot)));
+
+        let index_post = reserve.borrow_index;
+        let total_post = reserve.total_debt();
+        let position_post = reserve.position_debt(&position);
+
+        clog!(index_pre, total_pre, position_pre, slot);
+        clog!(index_post, total_post, position_post);
+
+        cvlr_assert_ge!(index_post, index_pre);
+        cvlr_assert_ge!(total_post, total_pre);
+        cvlr_assert_ge!(position_post, position_pre);
+    }
+
+    #[rule]
+    pub fn rule_lending_repay_all_zeroes_position() {
+        let mut reserve: LendingReserve = nondet();
+        let mut position = nondet_position(&reserve);
+
+        let debt_pre = reserve.position_debt(&position);
+
+        let amount = reserve.repay_all(&mut position);
+        clog!(stringify!(reserve.repay_all(&mut position)));
+
+        clog!(debt_pre, amount);
+
+        cvlr_assert_eq!(position.scaled_debt, 0);
+        cvlr_assert_eq!(reserve.position_debt(&position), 0);
+        // -- the borrower pays the whole debt
+        cvlr_assert_eq!(amount, debt_pre);
+    }
+}
@@ -1,4 +1,6 @@
 
+#[macro_use]
+mod macros;
 mod asserts;
 mod fee_spec;
 mod fee_spec_ok;
@@ -9,4 +11,17 @@ mod some_fee;
 mod very_simple_fee;
 mod inflation;
 pub mod certora;
-mod arithmetic;
\ No newline at end of file
+mod arithmetic;
+mod tiered_fee;
+mod mul_div;
+mod fixed_point;
+mod signed;
+mod nonlinear;
+mod amm_spec;
+mod stableswap;
+mod lending_spec;
+mod liquidation_spec;
+mod staking_spec;
+mod vesting_spec;
+mod oracle_price;
+mod decimals;
\ No newline at end of file
@@ -0,0 +1,323 @@
+use crate::mul_div::{mul_div_ceil_u128, mul_div_floor_u128};
+use std::cmp;
+
+/// Denominator of all ratios given in basis points
+const BASIS_POINTS: u64 = 10_000;
+
+/// Largest liquidation bonus allowed by [RiskParams::is_valid]
+const MAX_LIQUIDATION_BONUS_BPS: u16 = 2_000;
+
+/// Oracle prices and risk parameters of a market
+struct RiskParams {
+    /// Price of one unit of collateral in quote units
+    pub collateral_price: u64,
+    /// Price of one unit of debt in quote units
+    pub debt_price: u64,
+    /// Largest debt value a borrow may create, relative to the collateral value
+    pub ltv_bps: u16,
+    /// Debt value, relative to the collateral value, above which a position is
+    /// liquidatable
+    pub liquidation_threshold_bps: u16,
+    /// Largest share of the debt repaid by a single liquidation
+    pub close_factor_bps: u16,
+    /// Extra collateral given to the liquidator
+    pub liquidation_bonus_bps: u16,
+}
+
+impl RiskParams {
+    pub fn is_valid(&self) -> bool {
+        self.collateral_price > 0
+            && self.debt_price > 0
+            && self.ltv_bps <= self.liquidation_threshold_bps
+            && self.liquidation_threshold_bps as u64 <= BASIS_POINTS
+            && 0 < self.close_factor_bps
+            && self.close_factor_bps as u64 <= BASIS_POINTS
+            && self.liquidation_bonus_bps <= MAX_LIQUIDATION_BONUS_BPS
+    }
+
+    fn collateral_value(&self, amount: u64) -> u128 {
+        amount as u128 * self.collateral_price as u128
+    }
+
+    fn debt_value(&self, amount: u64) -> u128 {
+        amount as u128 * self.debt_price as u128
+    }
+
+    fn bonus_factor(&self) -> u64 {
+        BASIS_POINTS + self.liquidation_bonus_bps as u64
+    }
+}
+
+/// Collateral and debt of a single borrower
+struct Position {
+    pub collateral: u64,
+    pub debt: u64,
+}
+
+impl Position {
+    /// Debt value is at most the collateral value weighted by [bps]
+    fn is_within(&self, params: &RiskParams, bps: u16) -> bool {
+        let limit = mul_div_floor_u128(
+            params.collateral_value(self.collateral),
+            bps as u128,
+            BASIS_POINTS as u128,
+        )
+        .unwrap();
+        params.debt_value(self.debt) <= limit
+    }
+
+    pub fn is_healthy(&self, params: &RiskParams) -> bool {
+        self.is_within(params, params.liquidation_threshold_bps)
+    }
+
+    /// Collateral does not cover the debt plus the liquidation bonus
+    fn is_bad_debt(&self, params: &RiskParams) -> bool {
+        mul_div_floor_u128(
+            params.debt_value(self.debt),
+            params.bonus_factor() as u128,
+            BASIS_POINTS as u128,
+        )
+        .map_or(true, |v| params.collateral_value(self.collateral) <= v)
+    }
+
+    pub fn borrow(&mut self, params: &RiskParams, amount: u64) {
+        self.debt = self.debt.checked_add(amount).unwrap();
+        require!(self.is_within(params, params.ltv_bps));
+    }
+
+    /// Liquidate an unhealthy position, returns the repaid debt and the seized
+    /// collateral
+    ///
+    /// At most the close factor of the debt is repaid, rounded up, and the
+    /// seized collateral is rounded down. If the collateral cannot cover the
+    /// debt plus the bonus, all of it is seized and the position is closed with
+    /// the remaining debt written off. In that case [repay] is ignored: the
+    /// liquidator repays the debt that the seized collateral covers.
+    pub fn liquidate(&mut self, params: &RiskParams, repay: u64) -> (u64, u64) {
+        self.checked_liquidate(params, repay).unwrap()
+    }
+
+    /// Like [Position::liquidate], but returns an error instead of panicking
+    /// when the position is healthy or nothing is repaid
+    pub fn checked_liquidate(&mut self, params: &RiskParams, repay: u64) -> Result<(u64, u64), ()> {
+        if self.is_healthy(params) || repay == 0 {
+            return Err(());
+        }
+
+        if self.is_bad_debt(params) {
+            // -- bad debt: the whole position is closed, whatever repay is
+            let repaid = mul_div_ceil_u128(
+                params.collateral_value(self.collateral),
+                BASIS_POINTS as u128,
+                params.debt_price as u128 * params.bonus_factor() as u128,
+            )
+            .unwrap();
+            let repaid = cmp::min(repaid, self.debt as u128) as u64;
+            let seized = self.collateral;
+            self.collateral = 0;
+            self.debt = 0;
+            return Ok((repaid, seized));
+        }
+
+        let max_repay: u64 = mul_div_ceil_u128(
+            self.debt as u128,
+            params.close_factor_bps as u128,
+            BASIS_POINTS as u128,
+        )
+        .unwrap()
+        .try_into()
+        .unwrap();
+        let repaid = cmp::min(repay, max_repay);
+        let seized: u64 = mul_div_floor_u128(
+            params.debt_value(repaid),
+            params.bonus_factor() as u128,
+            params.collateral_price as u128 * BASIS_POINTS as u128,
+        )
+        .unwrap()
+        .try_into()
+        .unwrap();
+
+        self.debt = self.debt.checked_sub(repaid).unwrap();
+        self.collateral = self.collateral.checked_sub(seized).unwrap();
+        Ok((repaid, seized))
+    }
+}
+
+mod fv {
+    use super::*;
+    use cvlr::mathint::NativeInt as MathInt;
+    use cvlr::prelude::*;
+
+    struct FvPosition {
+        collateral_value: MathInt,
+        debt_value: MathInt,
+        debt: MathInt,
+    }
+
+    impl FvPosition {
+        pub fn new(position: &Position, params: &RiskParams) -> Self {
+            Self {
+                collateral_value: MathInt::from(position.collateral) * params.collateral_price,
+                debt_value: MathInt::from(position.debt) * params.debt_price,
+                debt: position.debt.into(),
+            }
+        }
+
+        pub fn assume_healthy(&self, params: &RiskParams) {
+            let p = self;
+            cvlr_assume!(
+                p.debt_value * BASIS_POINTS
+                    <= p.collateral_value * params.liquidation_threshold_bps as u64
+            );
+        }
+
+        pub fn assume_unhealthy(&self, params: &RiskParams) {
+            let p = self;
+            cvlr_assume!(
+                p.debt_value * BASIS_POINTS
+                    > p.collateral_value * params.liquidation_threshold_bps as u64
+            );
+        }
+
+        pub fn check_healthy(&self, params: &RiskParams) {
+            let p = self;
+            cvlr_assert_le!(
+                p.debt_value * BASIS_POINTS,
+                p.collateral_value * params.liquidation_threshold_bps as u64
+            );
+        }
+
+        /// Health factor `collateral_value * threshold / debt_value` strictly
+        /// increases, or the debt is fully closed
+        pub fn check_health_improved(&self, old: &FvPosition) {
+            let new = self;
+            if new.debt > MathInt::from(0u64) {
+                cvlr_assert_gt!(
+                    new.collateral_value * old.debt_value,
+                    old.collateral_value * new.debt_value
+                );
+            }
+        }
+    }
+
+    impl cvlr::nondet::Nondet for RiskParams {
+        fn nondet() -> Self {
+            let params = Self {
+                collateral_price: nondet(),
+                debt_price: nondet(),
+                ltv_bps: nondet(),
+                liquidation_threshold_bps: nondet(),
+                close_factor_bps: nondet(),
+                liquidation_bonus_bps: nondet(),
+            };
+            cvlr_assume!(params.is_valid());
+            params
+        }
+    }
+
+    impl cvlr::nondet::Nondet for Position {
+        fn nondet() -> Self {
+            Self {
+                collateral: nondet(),
+                debt: nondet(),
+            }
+        }
+    }
+
+    impl cvlr::log::CvlrLog for FvPosition {
+        #[inline(always)]
+        fn log(&self, tag: &str, logger: &mut cvlr::log::CvlrLogger) {
+            use cvlr::log::cvlr_log_with;
+            cvlr_log_with("", &tag, logger);
+            cvlr_log_with("\tcollateral_value", &self.collateral_value, logger);
+            cvlr_log_with("\tdebt_value", &self.debt_value, logger);
+            cvlr_log_with("\tdebt", &self.debt, logger);
+        }
+    }
+
+    #[rule]
+    pub fn rule_liquidation_healthy_cannot_liquidate() {
+        let params: RiskParams = nondet();
+        let mut position: Position = nondet();
+
+        let fv_position_pre = FvPosition::new(&position, &params);
+        fv_position_pre.assume_healthy(&params);
+
+        let repay: u64 = nondet();
+        let res = position.checked_liquidate(&params, repay);
+        clog!(stringify!(position.checked_liquidate(&params, repay);));
+
+        let fv_position_post = FvPosition::new(&position, &params);
+
+        clog!(fv_position_pre, repay, fv_position_post);
+
+        cvlr_assert!(res.is_err());
+        cvlr_assert!(fv_position_post.debt == fv_position_pre.debt);
+    }
+
+    #[rule]
+    pub fn rule_liquidation_borrow_within_ltv_is_healthy() {
+        let params: RiskParams = nondet();
+        let mut position: Position = nondet();
+
+        let amount: u64 = nondet();
+        position.borrow(&params, amount);
+        clog!(stringify!(position.borrow(&params, amount);));
+
+        let fv_position_post = FvPosition::new(&position, &params);
+
+        clog!(amount, fv_position_post);
+
+        fv_position_post.check_healthy(&params);
+    }
+
+    #[rule]
+    pub fn rule_liquidation_improves_health() {
+        let params: RiskParams = nondet();
+        let mut position: Position = nondet();
+
+        let fv_position_pre = FvPosition::new(&position, &params);
+        fv_position_pre.assume_unhealthy(&params);
+
+        let repay: u64 = nondet();
+        let (repaid, seized) = position.liquidate(&params, repay);
+        clog!(stringify!(position.liquidate(&params, repay)));
+        clog!(repaid, seized);
+
+        let fv_position_post = FvPosition::new(&position, &params);
+
+        clog!(fv_position_pre, repay, fv_position_post);
+
+        fv_position_post.check_health_improved(&fv_position_pre);
+    }
+
+    #[rule]
+    pub fn rule_liquidation_bonus_bounded() {
+        let params: RiskParams = nondet();
+        let mut position: Position = nondet();
+
+        let debt_pre = position.debt;
+
+        let repay: u64 = nondet();
+        let (repaid, seized) = position.liquidate(&params, repay);
+        clog!(stringify!(position.liquidate(&params, repay)));
+        clog!(debt_pre, repay, repaid, seized);
+
+        // -- seized value is at most the repaid value plus the bonus
+        let seized_value = MathInt::from(seized) * params.collateral_price;
+        let repaid_value = MathInt::from(repaid) * params.debt_price;
+        cvlr_assert_le!(
+            seized_value * BASIS_POINTS,
+            repaid_value * params.bonus_factor()
+        );
+
+        // -- unless the position is closed, the close factor is respected
+        if position.debt > 0 {
+            cvlr_assert_le!(repaid, repay);
+            cvlr_assert_le!(
+                MathInt::from(repaid) * BASIS_POINTS,
+                MathInt::from(debt_pre) * params.close_factor_bps as u64 + BASIS_POINTS
+            );
+        }
+    }
+}
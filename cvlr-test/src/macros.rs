@@ -0,0 +1,30 @@
+/// Panics unless `$lhs > $rhs`, like the `require!` checks of an on-chain
+/// program
+macro_rules! require_gt {
+    ($lhs: expr, $rhs: expr) => {
+        if $lhs > $rhs {
+        } else {
+            panic!()
+        }
+    };
+}
+
+/// Panics unless `$lhs <= $rhs`
+macro_rules! require_le {
+    ($lhs: expr, $rhs: expr) => {
+        if $lhs <= $rhs {
+        } else {
+            panic!()
+        }
+    };
+}
+
+/// Panics unless `$cond` holds
+macro_rules! require {
+    ($cond: expr) => {
+        if $cond {
+        } else {
+            panic!()
+        }
+    };
+}
@@ -0,0 +1,245 @@
+//! Shared `a * b / c` primitives with explicit rounding
+//!
+//! All functions compute the product at full width (u128 for u64 operands,
+//! 256 bits for u128 operands), so they only fail when `c == 0` or when the
+//! rounded quotient does not fit into the result type.
+
+use cvlr::mathint::NativeInt as MathInt;
+use cvlr::prelude::*;
+
+/// Rounding of the quotient `q` of a division by `c` with remainder `r`
+macro_rules! impl_round {
+    ($name:ident, $uint:ty) => {
+        fn $name(q: $uint, r: $uint, c: $uint, rounding: Rounding) -> Option<$uint> {
+            // -- `c - r` avoids computing `2 * r`, which might overflow
+            let up = match rounding {
+                Rounding::Floor => false,
+                Rounding::Ceil => r > 0,
+                Rounding::HalfUp => r >= c - r,
+                Rounding::HalfEven => r > c - r || (r == c - r && q % 2 == 1),
+            };
+            if up {
+                q.checked_add(1)
+            } else {
+                Some(q)
+            }
+        }
+    };
+}
+
+#[derive(Clone, Copy)]
+enum Rounding {
+    Floor,
+    Ceil,
+    HalfUp,
+    HalfEven,
+}
+
+impl_round!(round_u64, u64);
+impl_round!(round_u128, u128);
+
+fn mul_div_u64(a: u64, b: u64, c: u64, rounding: Rounding) -> Option<u64> {
+    if c == 0 {
+        return None;
+    }
+    let prod = (a as u128) * (b as u128);
+    let q: u64 = (prod / c as u128).try_into().ok()?;
+    let r = (prod % c as u128) as u64;
+    round_u64(q, r, c, rounding)
+}
+
+/// Full 256-bit product of [a] and [b] as `(hi, lo)`
+fn full_mul_u128(a: u128, b: u128) -> (u128, u128) {
+    const MASK: u128 = u64::MAX as u128;
+    let (a0, a1) = (a & MASK, a >> 64);
+    let (b0, b1) = (b & MASK, b >> 64);
+
+    let lo_lo = a0 * b0;
+    let lo_hi = a0 * b1;
+    let hi_lo = a1 * b0;
+    let hi_hi = a1 * b1;
+
+    // -- sum of three 64 bit numbers, cannot overflow
+    let mid = (lo_lo >> 64) + (lo_hi & MASK) + (hi_lo & MASK);
+    let lo = (lo_lo & MASK) | (mid << 64);
+    let hi = hi_hi + (lo_hi >> 64) + (hi_lo >> 64) + (mid >> 64);
+    (hi, lo)
+}
+
+/// Quotient and remainder of `(hi, lo) / c`, given that `hi < c`
+///
+/// Schoolbook binary long division. The condition `hi < c` guarantees that
+/// the quotient fits into 128 bits.
+fn div_rem_u256_by_u128(hi: u128, lo: u128, c: u128) -> (u128, u128) {
+    let mut rem = hi;
+    let mut q: u128 = 0;
+    for i in (0..128).rev() {
+        // -- the bit shifted out of `rem` is part of the partial remainder
+        let carry = rem >> 127;
+        rem = (rem << 1) | ((lo >> i) & 1);
+        q <<= 1;
+        if carry == 1 || rem >= c {
+            rem = rem.wrapping_sub(c);
+            q |= 1;
+        }
+    }
+    (q, rem)
+}
+
+fn mul_div_u128(a: u128, b: u128, c: u128, rounding: Rounding) -> Option<u128> {
+    if c == 0 {
+        return None;
+    }
+    let (hi, lo) = full_mul_u128(a, b);
+    if hi >= c {
+        // -- quotient does not fit into 128 bits
+        return None;
+    }
+    let (q, r) = div_rem_u256_by_u128(hi, lo, c);
+    round_u128(q, r, c, rounding)
+}
+
+/// `a * b / c` rounded down
+pub fn mul_div_floor_u64(a: u64, b: u64, c: u64) -> Option<u64> {
+    mul_div_u64(a, b, c, Rounding::Floor)
+}
+
+/// `a * b / c` rounded up
+pub fn mul_div_ceil_u64(a: u64, b: u64, c: u64) -> Option<u64> {
+    mul_div_u64(a, b, c, Rounding::Ceil)
+}
+
+/// `a * b / c` rounded to nearest, ties away from zero
+pub fn mul_div_round_half_up_u64(a: u64, b: u64, c: u64) -> Option<u64> {
+    mul_div_u64(a, b, c, Rounding::HalfUp)
+}
+
+/// `a * b / c` rounded to nearest, ties to even
+pub fn mul_div_round_half_even_u64(a: u64, b: u64, c: u64) -> Option<u64> {
+    mul_div_u64(a, b, c, Rounding::HalfEven)
+}
+
+/// `a * b / c` rounded down
+pub fn mul_div_floor_u128(a: u128, b: u128, c: u128) -> Option<u128> {
+    mul_div_u128(a, b, c, Rounding::Floor)
+}
+
+/// `a * b / c` rounded up
+pub fn mul_div_ceil_u128(a: u128, b: u128, c: u128) -> Option<u128> {
+    mul_div_u128(a, b, c, Rounding::Ceil)
+}
+
+/// `a * b / c` rounded to nearest, ties away from zero
+pub fn mul_div_round_half_up_u128(a: u128, b: u128, c: u128) -> Option<u128> {
+    mul_div_u128(a, b, c, Rounding::HalfUp)
+}
+
+/// `a * b / c` rounded to nearest, ties to even
+pub fn mul_div_round_half_even_u128(a: u128, b: u128, c: u128) -> Option<u128> {
+    mul_div_u128(a, b, c, Rounding::HalfEven)
+}
+
+/// Check [res] against the mathematical value [expected] of `a * b / c`
+///
+/// The result is `None` exactly when `c == 0` or [expected] is out of range
+fn check_mul_div(res: Option<MathInt>, expected: MathInt, c: MathInt, in_range: bool) {
+    clog!(res, expected);
+    match res {
+        Some(v) => {
+            cvlr_assert!(c > MathInt::from(0u64));
+            cvlr_assert_eq!(v, expected);
+        }
+        None => {
+            cvlr_assert!(c == MathInt::from(0u64) || !in_range);
+        }
+    }
+}
+
+/// Mathematical value of `a * b / c` rounded half to even
+fn math_mul_div_half_even(a: MathInt, b: MathInt, c: MathInt) -> MathInt {
+    let q = a.muldiv(b, c);
+    let twice_rem = (a * b - q * c) * 2;
+    let q_is_odd = q - (q / 2) * 2 == MathInt::from(1u64);
+    if twice_rem > c || (twice_rem == c && q_is_odd) {
+        q + 1
+    } else {
+        q
+    }
+}
+
+macro_rules! impl_mul_div_rules {
+    ($uint:ty, $is_uint:ident,
+     $floor:ident => $rule_floor:ident,
+     $ceil:ident => $rule_ceil:ident,
+     $half_up:ident => $rule_half_up:ident,
+     $half_even:ident => $rule_half_even:ident) => {
+        #[rule]
+        pub fn $rule_floor() {
+            let (a, b, c): ($uint, $uint, $uint) = (nondet(), nondet(), nondet());
+            clog!(a, b, c);
+            let (ma, mb, mc) = (MathInt::from(a), MathInt::from(b), MathInt::from(c));
+            let expected = ma.muldiv(mb, mc);
+            check_mul_div(
+                $floor(a, b, c).map(MathInt::from),
+                expected,
+                mc,
+                expected.$is_uint(),
+            );
+        }
+
+        #[rule]
+        pub fn $rule_ceil() {
+            let (a, b, c): ($uint, $uint, $uint) = (nondet(), nondet(), nondet());
+            clog!(a, b, c);
+            let (ma, mb, mc) = (MathInt::from(a), MathInt::from(b), MathInt::from(c));
+            let expected = ma.muldiv_ceil(mb, mc);
+            check_mul_div(
+                $ceil(a, b, c).map(MathInt::from),
+                expected,
+                mc,
+                expected.$is_uint(),
+            );
+        }
+
+        #[rule]
+        pub fn $rule_half_up() {
+            let (a, b, c): ($uint, $uint, $uint) = (nondet(), nondet(), nondet());
+            clog!(a, b, c);
+            let (ma, mb, mc) = (MathInt::from(a), MathInt::from(b), MathInt::from(c));
+            // -- floor((2ab + c) / 2c) is ab/c rounded half up
+            let expected = (ma * mb * 2 + mc) / (mc * 2);
+            check_mul_div(
+                $half_up(a, b, c).map(MathInt::from),
+                expected,
+                mc,
+                expected.$is_uint(),
+            );
+        }
+
+        #[rule]
+        pub fn $rule_half_even() {
+            let (a, b, c): ($uint, $uint, $uint) = (nondet(), nondet(), nondet());
+            clog!(a, b, c);
+            let (ma, mb, mc) = (MathInt::from(a), MathInt::from(b), MathInt::from(c));
+            let expected = math_mul_div_half_even(ma, mb, mc);
+            check_mul_div(
+                $half_even(a, b, c).map(MathInt::from),
+                expected,
+                mc,
+                expected.$is_uint(),
+            );
+        }
+    };
+}
+
+impl_mul_div_rules!(u64, is_u64,
+    mul_div_floor_u64 => rule_mul_div_floor_u64,
+    mul_div_ceil_u64 => rule_mul_div_ceil_u64,
+    mul_div_round_half_up_u64 => rule_mul_div_round_half_up_u64,
+    mul_div_round_half_even_u64 => rule_mul_div_round_half_even_u64);
+
+impl_mul_div_rules!(u128, is_u128,
+    mul_div_floor_u128 => rule_mul_div_floor_u128,
+    mul_div_ceil_u128 => rule_mul_div_ceil_u128,
+    mul_div_round_half_up_u128 => rule_mul_div_round_half_up_u128,
+    mul_div_round_half_even_u128 => rule_mul_div_round_half_even_u128);
@@ -0,0 +1,241 @@
+//! Non-linear helpers for AMM math: integer square root and powers
+//!
+//! All loops are explicitly bounded so that the prover can unroll them.
+//! Functions whose loop might not converge within the bound return `None`,
+//! and rules prove that this never happens.
+
+use cvlr::mathint::NativeInt as MathInt;
+use cvlr::prelude::*;
+use cvlr_fixed::native_fixed::NativeFixedU128;
+
+/// Newton iterations needed for any u64, including the final check
+const NEWTON_MAX_ITER_U64: u32 = 6;
+/// Newton iterations needed for any u128, including the final check
+const NEWTON_MAX_ITER_U128: u32 = 7;
+
+/// Largest exponent used by rules about powers
+const MAX_RULE_EXP: u32 = 4;
+
+macro_rules! impl_isqrt {
+    ($uint:ty, $newton:ident, $bitwise:ident, $max_iter:expr) => {
+        /// Integer square root by Newton's method
+        ///
+        /// The initial guess is a power of two that is at least `sqrt(x)`, from
+        /// which the iteration decreases monotonically to `floor(sqrt(x))`.
+        /// Returns `None` if it does not converge within the iteration bound.
+        pub fn $newton(x: $uint) -> Option<$uint> {
+            if x < 2 {
+                return Some(x);
+            }
+            let bits = <$uint>::BITS - x.leading_zeros();
+            let mut r: $uint = 1 << ((bits + 1) / 2);
+            for _ in 0..$max_iter {
+                let next = (r + x / r) / 2;
+                if next >= r {
+                    return Some(r);
+                }
+                r = next;
+            }
+            None
+        }
+
+        /// Integer square root computed digit by digit
+        ///
+        /// Determines one bit of the result per iteration, so the loop runs
+        /// exactly `BITS / 2` times.
+        pub fn $bitwise(x: $uint) -> $uint {
+            let mut op = x;
+            let mut res: $uint = 0;
+            let mut one: $uint = 1 << (<$uint>::BITS - 2);
+            for _ in 0..<$uint>::BITS / 2 {
+                if op >= res + one {
+                    op -= res + one;
+                    res = (res >> 1) + one;
+                } else {
+                    res >>= 1;
+                }
+                one >>= 2;
+            }
+            res
+        }
+    };
+}
+
+impl_isqrt!(
+    u64,
+    isqrt_newton_u64,
+    isqrt_bitwise_u64,
+    NEWTON_MAX_ITER_U64
+);
+impl_isqrt!(
+    u128,
+    isqrt_newton_u128,
+    isqrt_bitwise_u128,
+    NEWTON_MAX_ITER_U128
+);
+
+/// `base^exp` by square-and-multiply, `None` on overflow
+///
+/// The loop runs once per bit of [exp].
+pub fn checked_pow_u64(base: u64, exp: u32) -> Option<u64> {
+    let mut result: u64 = 1;
+    let mut b = base;
+    let mut e = exp;
+    for _ in 0..u32::BITS {
+        if e & 1 == 1 {
+            result = result.checked_mul(b)?;
+        }
+        e >>= 1;
+        if e == 0 {
+            break;
+        }
+        b = b.checked_mul(b)?;
+    }
+    Some(result)
+}
+
+/// `x^exp` of a fixed-point number by square-and-multiply
+///
+/// Every multiplication rounds down, so the result is at most the exact power.
+pub fn pow_fixed<const F: u32>(x: NativeFixedU128<F>, exp: u32) -> NativeFixedU128<F> {
+    let mut result = NativeFixedU128::<F>::one();
+    let mut b = x;
+    let mut e = exp;
+    for _ in 0..u32::BITS {
+        if e & 1 == 1 {
+            result = result * b;
+        }
+        e >>= 1;
+        if e == 0 {
+            break;
+        }
+        b = b * b;
+    }
+    result
+}
+
+/// Fixed-point number used by the rules
+type FpNum = NativeFixedU128<32>;
+
+/// `r * r <= x < (r + 1) * (r + 1)`
+fn check_isqrt(x: MathInt, r: MathInt) {
+    cvlr_assert_le!(r * r, x);
+    cvlr_assert_lt!(x, (r + 1) * (r + 1));
+}
+
+#[rule]
+pub fn rule_isqrt_newton_u64() {
+    let x: u64 = nondet();
+    let r = isqrt_newton_u64(x);
+    clog!(x, r);
+    // -- converges within the iteration bound
+    cvlr_assert!(r.is_some());
+    check_isqrt(x.into(), r.unwrap().into());
+}
+
+#[rule]
+pub fn rule_isqrt_bitwise_u64() {
+    let x: u64 = nondet();
+    let r = isqrt_bitwise_u64(x);
+    clog!(x, r);
+    check_isqrt(x.into(), r.into());
+}
+
+#[rule]
+pub fn rule_isqrt_agree_u64() {
+    let x: u64 = nondet();
+    let r1 = isqrt_newton_u64(x).unwrap();
+    let r2 = isqrt_bitwise_u64(x);
+    clog!(x, r1, r2);
+    cvlr_assert_eq!(r1, r2);
+}
+
+#[rule]
+pub fn rule_isqrt_newton_u128() {
+    let x: u128 = nondet();
+    let r = isqrt_newton_u128(x);
+    clog!(x, r);
+    // -- converges within the iteration bound
+    cvlr_assert!(r.is_some());
+    check_isqrt(x.into(), r.unwrap().into());
+}
+
+#[rule]
+pub fn rule_isqrt_bitwise_u128() {
+    let x: u128 = nondet();
+    let r = isqrt_bitwise_u128(x);
+    clog!(x, r);
+    check_isqrt(x.into(), r.into());
+}
+
+#[rule]
+pub fn rule_isqrt_agree_u128() {
+    let x: u128 = nondet();
+    let r1 = isqrt_newton_u128(x).unwrap();
+    let r2 = isqrt_bitwise_u128(x);
+    clog!(x, r1, r2);
+    cvlr_assert_eq!(r1, r2);
+}
+
+#[rule]
+pub fn rule_checked_pow_u64() {
+    let base: u64 = nondet();
+    let exp: u32 = nondet();
+    cvlr_assume!(exp <= MAX_RULE_EXP);
+    clog!(base, exp);
+
+    let mut expected = MathInt::from(1u64);
+    for _ in 0..exp {
+        expected = expected * base;
+    }
+
+    match checked_pow_u64(base, exp) {
+        Some(v) => cvlr_assert_eq!(MathInt::from(v), expected),
+        None => cvlr_assert!(!expected.is_u64()),
+    }
+}
+
+#[rule]
+pub fn rule_pow_fixed_identities() {
+    let x: FpNum = nondet();
+    clog!(x);
+    cvlr_assert_eq!(pow_fixed(x, 0), FpNum::one());
+    cvlr_assert_eq!(pow_fixed(x, 1), x);
+}
+
+#[rule]
+pub fn rule_pow_fixed_rounds_down() {
+    let x: FpNum = nondet();
+    let exp: u32 = nondet();
+    cvlr_assume!(0 < exp && exp <= MAX_RULE_EXP);
+    clog!(x, exp);
+
+    // -- exact x^exp scaled by BASE^exp, compared to the result scaled the same
+    let base = MathInt::from(FpNum::one().to_bits());
+    let mut exact = MathInt::from(1u64);
+    let mut scale = MathInt::from(1u64);
+    for _ in 0..exp {
+        exact = exact * MathInt::from(x.to_bits());
+        scale = scale * base;
+    }
+    let res = pow_fixed(x, exp);
+    clog!(res);
+    cvlr_assert_le!(MathInt::from(res.to_bits()) * scale, exact * base);
+}
+
+#[rule]
+pub fn rule_pow_fixed_monotone() {
+    let x1: FpNum = nondet();
+    let x2: FpNum = nondet();
+    let exp: u32 = nondet();
+    cvlr_assume!(x1 <= x2);
+    cvlr_assume!(exp <= MAX_RULE_EXP);
+    clog!(x1, x2, exp);
+
+    cvlr_assert_le!(pow_fixed(x1, exp), pow_fixed(x2, exp));
+
+    // -- for x >= 1, higher powers are larger
+    if x1 >= FpNum::one() {
+        cvlr_assert_le!(pow_fixed(x1, exp), pow_fixed(x1, exp + 1));
+    }
+}
@@ -0,0 +1,284 @@
+//! Pyth-style oracle prices and conversion of token amounts between mints
+//!
+//! A price is `price * 10^expo` units of quote token per unit of base token,
+//! with a confidence interval of `conf * 10^expo`. Amounts are converted
+//! conservatively: at the bottom of the interval rounding down (bid), or at the
+//! top of the interval rounding up (ask).
+
+use crate::mul_div::{mul_div_ceil_u128, mul_div_floor_u128};
+use cvlr::mathint::NativeInt as MathInt;
+use cvlr::prelude::*;
+
+pub const MIN_EXPO: i32 = -18;
+pub const MAX_EXPO: i32 = 18;
+pub const MAX_DECIMALS: u8 = 18;
+
+#[derive(Clone, Copy)]
+pub struct Price {
+    pub price: i64,
+    pub conf: u64,
+    pub expo: i32,
+}
+
+impl Price {
+    /// Prices for which conversions cannot overflow internally
+    ///
+    /// The confidence interval must not be wider than the price itself, so that
+    /// `price + conf` fits into 64 bits.
+    pub fn is_supported(&self) -> bool {
+        self.price > 0
+            && self.conf <= self.price as u64
+            && (MIN_EXPO..=MAX_EXPO).contains(&self.expo)
+    }
+
+    fn mid_mantissa(&self) -> u128 {
+        self.price as u128
+    }
+
+    fn bid_mantissa(&self) -> u128 {
+        (self.price as u128).saturating_sub(self.conf as u128)
+    }
+
+    fn ask_mantissa(&self) -> u128 {
+        self.price as u128 + self.conf as u128
+    }
+
+    /// `10^(expo + quote_decimals - base_decimals)` as a fraction
+    fn scale(&self, base_decimals: u8, quote_decimals: u8) -> Option<(u128, u128)> {
+        let e = self.expo as i64 + quote_decimals as i64 - base_decimals as i64;
+        let pow = 10u128.checked_pow(e.unsigned_abs().try_into().ok()?)?;
+        if e >= 0 {
+            Some((pow, 1))
+        } else {
+            Some((1, pow))
+        }
+    }
+
+    fn convert(
+        &self,
+        amount: u64,
+        mantissa: u128,
+        base_decimals: u8,
+        quote_decimals: u8,
+        mul_div: fn(u128, u128, u128) -> Option<u128>,
+    ) -> Option<u64> {
+        let (num, den) = self.scale(base_decimals, quote_decimals)?;
+        let value = (amount as u128).checked_mul(mantissa)?;
+        mul_div(value, num, den)?.try_into().ok()
+    }
+
+    /// Quote value of [amount] base tokens at the price, rounded down
+    pub fn value_mid(&self, amount: u64, base_decimals: u8, quote_decimals: u8) -> Option<u64> {
+        let mantissa = self.mid_mantissa();
+        self.convert(
+            amount,
+            mantissa,
+            base_decimals,
+            quote_decimals,
+            mul_div_floor_u128,
+        )
+    }
+
+    /// Quote value of [amount] base tokens at the bottom of the confidence
+    /// interval, rounded down
+    ///
+    /// Use to value assets held by the protocol, such as collateral.
+    pub fn value_bid(&self, amount: u64, base_decimals: u8, quote_decimals: u8) -> Option<u64> {
+        let mantissa = self.bid_mantissa();
+        self.convert(
+            amount,
+            mantissa,
+            base_decimals,
+            quote_decimals,
+            mul_div_floor_u128,
+        )
+    }
+
+    /// Quote value of [amount] base tokens at the top of the confidence
+    /// interval, rounded up
+    ///
+    /// Use to value liabilities of the protocol, such as debt.
+    pub fn value_ask(&self, amount: u64, base_decimals: u8, quote_decimals: u8) -> Option<u64> {
+        let mantissa = self.ask_mantissa();
+        self.convert(
+            amount,
+            mantissa,
+            base_decimals,
+            quote_decimals,
+            mul_div_ceil_u128,
+        )
+    }
+}
+
+impl cvlr::nondet::Nondet for Price {
+    fn nondet() -> Self {
+        let price = Self {
+            price: nondet(),
+            conf: nondet(),
+            expo: nondet(),
+        };
+        cvlr_assume!(price.is_supported());
+        price
+    }
+}
+
+impl cvlr::log::CvlrLog for Price {
+    #[inline(always)]
+    fn log(&self, tag: &str, logger: &mut cvlr::log::CvlrLogger) {
+        use cvlr::log::cvlr_log_with;
+        cvlr_log_with("", &tag, logger);
+        cvlr_log_with("\tprice", &self.price, logger);
+        cvlr_log_with("\tconf", &self.conf, logger);
+        cvlr_log_with("\texpo", &self.expo, logger);
+    }
+}
+
+/// Amount and decimals of a conversion in the supported range
+fn nondet_conversion() -> (u64, u8, u8) {
+    let amount: u64 = nondet();
+    let base_decimals: u8 = nondet();
+    let quote_decimals: u8 = nondet();
+    cvlr_assume!(base_decimals <= MAX_DECIMALS);
+    cvlr_assume!(quote_decimals <= MAX_DECIMALS);
+    clog!(amount, base_decimals, quote_decimals);
+    (amount, base_decimals, quote_decimals)
+}
+
+/// Exact value of [amount] at [mantissa], as the fraction `num / den`
+fn exact_value(
+    price: &Price,
+    amount: u64,
+    mantissa: u128,
+    base_decimals: u8,
+    quote_decimals: u8,
+) -> (MathInt, MathInt) {
+    let (num, den) = price.scale(base_decimals, quote_decimals).unwrap();
+    (
+        MathInt::from(amount) * MathInt::from(mantissa) * MathInt::from(num),
+        MathInt::from(den),
+    )
+}
+
+#[rule]
+pub fn rule_oracle_bid_rounds_down() {
+    let price: Price = nondet();
+    let (amount, base_decimals, quote_decimals) = nondet_conversion();
+    clog!(price);
+
+    let (num, den) = exact_value(
+        &price,
+        amount,
+        price.bid_mantissa(),
+        base_decimals,
+        quote_decimals,
+    );
+    let v = MathInt::from(
+        price
+            .value_bid(amount, base_decimals, quote_decimals)
+            .unwrap(),
+    );
+    clog!(num, den, v);
+
+    cvlr_assert_le!(v * den, num);
+    cvlr_assert_lt!(num, (v + 1) * den);
+}
+
+#[rule]
+pub fn rule_oracle_ask_rounds_up() {
+    let price: Price = nondet();
+    let (amount, base_decimals, quote_decimals) = nondet_conversion();
+    clog!(price);
+
+    let (num, den) = exact_value(
+        &price,
+        amount,
+        price.ask_mantissa(),
+        base_decimals,
+        quote_decimals,
+    );
+    let v = MathInt::from(
+        price
+            .value_ask(amount, base_decimals, quote_decimals)
+            .unwrap(),
+    );
+    clog!(num, den, v);
+
+    cvlr_assert_ge!(v * den, num);
+    cvlr_assert_gt!(num + den, v * den);
+}
+
+#[rule]
+pub fn rule_oracle_bid_le_mid_le_ask() {
+    let price: Price = nondet();
+    let (amount, base_decimals, quote_decimals) = nondet_conversion();
+    clog!(price);
+
+    let bid = price
+        .value_bid(amount, base_decimals, quote_decimals)
+        .unwrap();
+    let mid = price
+        .value_mid(amount, base_decimals, quote_decimals)
+        .unwrap();
+    let ask = price
+        .value_ask(amount, base_decimals, quote_decimals)
+        .unwrap();
+    clog!(bid, mid, ask);
+
+    cvlr_assert_le!(bid, mid);
+    cvlr_assert_le!(mid, ask);
+}
+
+/// The spread between bid and ask is the confidence interval, plus rounding
+#[rule]
+pub fn rule_oracle_spread_bounded() {
+    let price: Price = nondet();
+    let (amount, base_decimals, quote_decimals) = nondet_conversion();
+    clog!(price);
+
+    let bid = price
+        .value_bid(amount, base_decimals, quote_decimals)
+        .unwrap();
+    let ask = price
+        .value_ask(amount, base_decimals, quote_decimals)
+        .unwrap();
+    clog!(bid, ask);
+
+    let (conf, den) = exact_value(
+        &price,
+        amount,
+        price.conf as u128,
+        base_decimals,
+        quote_decimals,
+    );
+    let spread = MathInt::from(ask) - MathInt::from(bid);
+    cvlr_assert_lt!(spread * den, conf * 2 + den * 2);
+}
+
+/// Conversions fail exactly when the rounded value does not fit into u64
+#[rule]
+pub fn rule_oracle_no_overflow() {
+    let price: Price = nondet();
+    let (amount, base_decimals, quote_decimals) = nondet_conversion();
+    clog!(price);
+
+    let (bid_num, den) = exact_value(
+        &price,
+        amount,
+        price.bid_mantissa(),
+        base_decimals,
+        quote_decimals,
+    );
+    let (ask_num, _) = exact_value(
+        &price,
+        amount,
+        price.ask_mantissa(),
+        base_decimals,
+        quote_decimals,
+    );
+    let bid = price.value_bid(amount, base_decimals, quote_decimals);
+    let ask = price.value_ask(amount, base_decimals, quote_decimals);
+    clog!(bid, ask);
+
+    cvlr_assert_eq!(bid.is_some(), (bid_num / den).is_u64());
+    cvlr_assert_eq!(ask.is_some(), ask_num.div_ceil(den).is_u64());
+}
@@ -0,0 +1,496 @@
+//! Signed arithmetic used in PnL and funding-rate math
+//!
+//! Covers signed integer division with explicit rounding, checked and
+//! saturating operations on `i64` and `i128`, and a sign-magnitude fixed-point
+//! type built on top of [NativeFixedU128].
+
+use cvlr::prelude::*;
+use cvlr_fixed::native_fixed::NativeFixedU128;
+use std::cmp::Ordering;
+
+macro_rules! impl_signed_div {
+    ($int:ty, $div_floor:ident, $div_ceil:ident) => {
+        /// Division rounded toward negative infinity
+        ///
+        /// Unlike `/`, which truncates toward zero, `-7 / 2` is `-4`.
+        /// Returns `None` on division by zero or overflow.
+        pub fn $div_floor(a: $int, b: $int) -> Option<$int> {
+            let q = a.checked_div(b)?;
+            if a % b != 0 && ((a < 0) != (b < 0)) {
+                Some(q - 1)
+            } else {
+                Some(q)
+            }
+        }
+
+        /// Division rounded toward positive infinity
+        ///
+        /// Returns `None` on division by zero or overflow.
+        pub fn $div_ceil(a: $int, b: $int) -> Option<$int> {
+            let q = a.checked_div(b)?;
+            if a % b != 0 && ((a < 0) == (b < 0)) {
+                Some(q + 1)
+            } else {
+                Some(q)
+            }
+        }
+    };
+}
+
+impl_signed_div!(i64, div_floor_i64, div_ceil_i64);
+impl_signed_div!(i128, div_floor_i128, div_ceil_i128);
+
+macro_rules! impl_signed_overflow {
+    ($int:ty, $add_overflows:ident, $sub_overflows:ident, $mul_overflows:ident) => {
+        /// True if `a + b` is out of range
+        ///
+        /// Decided without a wider type, so that it also covers `i128`.
+        pub fn $add_overflows(a: $int, b: $int) -> bool {
+            (b > 0 && a > <$int>::MAX - b) || (b < 0 && a < <$int>::MIN - b)
+        }
+
+        /// True if `a - b` is out of range
+        pub fn $sub_overflows(a: $int, b: $int) -> bool {
+            (b < 0 && a > <$int>::MAX + b) || (b > 0 && a < <$int>::MIN + b)
+        }
+
+        /// True if `a * b` is out of range
+        pub fn $mul_overflows(a: $int, b: $int) -> bool {
+            match b {
+                0 => false,
+                -1 => a == <$int>::MIN,
+                _ => a.wrapping_mul(b) / b != a,
+            }
+        }
+    };
+}
+
+impl_signed_overflow!(i64, add_overflows_i64, sub_overflows_i64, mul_overflows_i64);
+impl_signed_overflow!(
+    i128,
+    add_overflows_i128,
+    sub_overflows_i128,
+    mul_overflows_i128
+);
+
+/// Signed fixed-point number with F bits of precision
+///
+/// Represented as a sign and a [NativeFixedU128] magnitude. Zero is never
+/// negative, so that every value has a unique representation.
+/// Multiplication and division round the magnitude down, i.e., toward zero.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct NativeFixedI128<const F: u32> {
+    neg: bool,
+    mag: NativeFixedU128<F>,
+}
+
+impl<const F: u32> NativeFixedI128<F> {
+    pub fn new(neg: bool, mag: NativeFixedU128<F>) -> Self {
+        let neg = neg && !Self::is_zero_mag(&mag);
+        Self { neg, mag }
+    }
+
+    pub fn zero() -> Self {
+        Self::new(false, NativeFixedU128::from_bits(0))
+    }
+
+    pub fn from_i64(v: i64) -> Self {
+        Self::new(v < 0, NativeFixedU128::from(v.unsigned_abs()))
+    }
+
+    fn is_zero_mag(mag: &NativeFixedU128<F>) -> bool {
+        mag.to_bits() == 0
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.neg
+    }
+
+    pub fn abs(&self) -> NativeFixedU128<F> {
+        self.mag
+    }
+
+    pub fn neg(&self) -> Self {
+        Self::new(!self.neg, self.mag)
+    }
+
+    pub fn checked_add(&self, v: Self) -> Self {
+        if self.neg == v.neg {
+            Self::new(self.neg, self.mag + v.mag)
+        } else if self.mag >= v.mag {
+            Self::new(self.neg, self.mag - v.mag)
+        } else {
+            Self::new(v.neg, v.mag - self.mag)
+        }
+    }
+
+    pub fn checked_sub(&self, v: Self) -> Self {
+        self.checked_add(v.neg())
+    }
+
+    pub fn checked_mul(&self, v: Self) -> Self {
+        Self::new(self.neg != v.neg, self.mag * v.mag)
+    }
+
+    pub fn checked_div(&self, v: Self) -> Self {
+        Self::new(self.neg != v.neg, self.mag / v.mag)
+    }
+
+    fn to_signed(neg: bool, mag: cvlr::mathint::NativeInt) -> Option<i128> {
+        let mag: u128 = mag.into();
+        if neg {
+            0i128.checked_sub_unsigned(mag)
+        } else {
+            i128::try_from(mag).ok()
+        }
+    }
+
+    /// Integer part rounded toward negative infinity
+    pub fn to_floor(self) -> Option<i128> {
+        if self.neg {
+            Self::to_signed(true, self.mag.to_ceil())
+        } else {
+            Self::to_signed(false, self.mag.to_floor())
+        }
+    }
+
+    /// Integer part rounded toward zero
+    pub fn to_trunc(self) -> Option<i128> {
+        Self::to_signed(self.neg, self.mag.to_floor())
+    }
+
+    /// Integer part rounded toward positive infinity
+    pub fn to_ceil(self) -> Option<i128> {
+        if self.neg {
+            Self::to_signed(true, self.mag.to_floor())
+        } else {
+            Self::to_signed(false, self.mag.to_ceil())
+        }
+    }
+}
+
+impl<const F: u32> cvlr::nondet::Nondet for NativeFixedI128<F> {
+    fn nondet() -> Self {
+        Self::new(nondet(), nondet())
+    }
+}
+
+impl<const F: u32> cvlr::log::CvlrLog for NativeFixedI128<F> {
+    #[inline(always)]
+    fn log(&self, tag: &str, logger: &mut cvlr::log::CvlrLogger) {
+        use cvlr::log::cvlr_log_with;
+        cvlr_log_with("", &tag, logger);
+        cvlr_log_with("\tnegative", &self.neg, logger);
+        cvlr_log_with("\tmagnitude", &self.mag, logger);
+    }
+}
+
+impl<const F: u32> PartialOrd for NativeFixedI128<F> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<const F: u32> Ord for NativeFixedI128<F> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.neg, other.neg) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => self.mag.cmp(&other.mag),
+            (true, true) => other.mag.cmp(&self.mag),
+        }
+    }
+}
+
+impl<const F: u32> core::ops::Add for NativeFixedI128<F> {
+    type Output = Self;
+
+    fn add(self, v: Self) -> Self::Output {
+        self.checked_add(v)
+    }
+}
+
+impl<const F: u32> core::ops::Sub for NativeFixedI128<F> {
+    type Output = Self;
+
+    fn sub(self, v: Self) -> Self::Output {
+        self.checked_sub(v)
+    }
+}
+
+impl<const F: u32> core::ops::Mul for NativeFixedI128<F> {
+    type Output = Self;
+
+    fn mul(self, v: Self) -> Self::Output {
+        self.checked_mul(v)
+    }
+}
+
+impl<const F: u32> core::ops::Div for NativeFixedI128<F> {
+    type Output = Self;
+
+    fn div(self, v: Self) -> Self::Output {
+        self.checked_div(v)
+    }
+}
+
+impl<const F: u32> core::ops::Neg for NativeFixedI128<F> {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        NativeFixedI128::neg(&self)
+    }
+}
+
+/// Signed fixed-point number used by the rules
+type SFpNum = NativeFixedI128<32>;
+
+/// Nondet `(a, b)` for which `a / b` is defined
+fn nondet_i64_div_args() -> (i64, i64) {
+    let a: i64 = nondet();
+    let b: i64 = nondet();
+    cvlr_assume!(b != 0);
+    cvlr_assume!(!(a == i64::MIN && b == -1));
+    clog!(a, b);
+    (a, b)
+}
+
+#[rule]
+pub fn rule_div_floor_i64() {
+    let (a, b) = nondet_i64_div_args();
+    let q = div_floor_i64(a, b).unwrap();
+    clog!(q);
+
+    // -- q * b <= a < (q + 1) * b, with the inequalities flipped for b < 0
+    let (a, b, q) = (a as i128, b as i128, q as i128);
+    if b > 0 {
+        cvlr_assert_le!(q * b, a);
+        cvlr_assert_lt!(a, (q + 1) * b);
+    } else {
+        cvlr_assert_ge!(q * b, a);
+        cvlr_assert_gt!(a, (q + 1) * b);
+    }
+}
+
+#[rule]
+pub fn rule_div_ceil_i64() {
+    let (a, b) = nondet_i64_div_args();
+    let q = div_ceil_i64(a, b).unwrap();
+    clog!(q);
+
+    // -- (q - 1) * b < a <= q * b, with the inequalities flipped for b < 0
+    let (a, b, q) = (a as i128, b as i128, q as i128);
+    if b > 0 {
+        cvlr_assert_lt!((q - 1) * b, a);
+        cvlr_assert_le!(a, q * b);
+    } else {
+        cvlr_assert_gt!((q - 1) * b, a);
+        cvlr_assert_ge!(a, q * b);
+    }
+}
+
+#[rule]
+pub fn rule_div_trunc_i64() {
+    let (a, b) = nondet_i64_div_args();
+    let q = a / b;
+    clog!(q);
+
+    // -- truncation never moves away from zero
+    let (a, b, q) = (a as i128, b as i128, q as i128);
+    let r = a - q * b;
+    cvlr_assert_lt!(r.abs(), b.abs());
+    cvlr_assert!(r == 0 || (r < 0) == (a < 0));
+    cvlr_assert_le!(div_floor_i64(a as i64, b as i64).unwrap() as i128, q);
+    cvlr_assert_ge!(div_ceil_i64(a as i64, b as i64).unwrap() as i128, q);
+}
+
+#[rule]
+pub fn rule_div_rounding_i128() {
+    let a: i128 = nondet();
+    let b: i128 = nondet();
+    cvlr_assume!(b != 0);
+    cvlr_assume!(!(a == i128::MIN && b == -1));
+    clog!(a, b);
+
+    let floor = div_floor_i128(a, b).unwrap();
+    let trunc = a / b;
+    let ceil = div_ceil_i128(a, b).unwrap();
+    clog!(floor, trunc, ceil);
+
+    cvlr_assert_le!(floor, trunc);
+    cvlr_assert_le!(trunc, ceil);
+    if a % b == 0 {
+        cvlr_assert_eq!(floor, ceil);
+    } else {
+        cvlr_assert_eq!(ceil - floor, 1);
+    }
+}
+
+#[rule]
+pub fn rule_checked_ops_i64() {
+    let a: i64 = nondet();
+    let b: i64 = nondet();
+    clog!(a, b);
+
+    let (wa, wb) = (a as i128, b as i128);
+    let in_range = |v: i128| i64::MIN as i128 <= v && v <= i64::MAX as i128;
+
+    match a.checked_add(b) {
+        Some(v) => cvlr_assert_eq!(v as i128, wa + wb),
+        None => cvlr_assert!(!in_range(wa + wb)),
+    }
+    match a.checked_sub(b) {
+        Some(v) => cvlr_assert_eq!(v as i128, wa - wb),
+        None => cvlr_assert!(!in_range(wa - wb)),
+    }
+    match a.checked_mul(b) {
+        Some(v) => cvlr_assert_eq!(v as i128, wa * wb),
+        None => cvlr_assert!(!in_range(wa * wb)),
+    }
+}
+
+#[rule]
+pub fn rule_saturating_ops_i64() {
+    let a: i64 = nondet();
+    let b: i64 = nondet();
+    clog!(a, b);
+
+    let (wa, wb) = (a as i128, b as i128);
+    let clamp = |v: i128| v.clamp(i64::MIN as i128, i64::MAX as i128);
+
+    cvlr_assert_eq!(a.saturating_add(b) as i128, clamp(wa + wb));
+    cvlr_assert_eq!(a.saturating_sub(b) as i128, clamp(wa - wb));
+    cvlr_assert_eq!(a.saturating_mul(b) as i128, clamp(wa * wb));
+}
+
+/// The overflow helpers agree with arithmetic in a wider type
+#[rule]
+pub fn rule_overflow_helpers_i64() {
+    let a: i64 = nondet();
+    let b: i64 = nondet();
+    clog!(a, b);
+
+    let (wa, wb) = (a as i128, b as i128);
+    let in_range = |v: i128| i64::MIN as i128 <= v && v <= i64::MAX as i128;
+
+    cvlr_assert_eq!(add_overflows_i64(a, b), !in_range(wa + wb));
+    cvlr_assert_eq!(sub_overflows_i64(a, b), !in_range(wa - wb));
+    cvlr_assert_eq!(mul_overflows_i64(a, b), !in_range(wa * wb));
+}
+
+#[rule]
+pub fn rule_checked_ops_i128() {
+    let a: i128 = nondet();
+    let b: i128 = nondet();
+    clog!(a, b);
+
+    match a.checked_add(b) {
+        Some(v) => cvlr_assert!(!add_overflows_i128(a, b) && v.wrapping_sub(b) == a),
+        None => cvlr_assert!(add_overflows_i128(a, b)),
+    }
+    match a.checked_sub(b) {
+        Some(v) => cvlr_assert!(!sub_overflows_i128(a, b) && v.wrapping_add(b) == a),
+        None => cvlr_assert!(sub_overflows_i128(a, b)),
+    }
+    match a.checked_mul(b) {
+        Some(v) => cvlr_assert!(!mul_overflows_i128(a, b) && v == a.wrapping_mul(b)),
+        None => cvlr_assert!(mul_overflows_i128(a, b)),
+    }
+}
+
+#[rule]
+pub fn rule_saturating_ops_i128() {
+    let a: i128 = nondet();
+    let b: i128 = nondet();
+    clog!(a, b);
+
+    // -- on overflow, the result is the bound on the side of the exact value
+    let bound = |positive: bool| if positive { i128::MAX } else { i128::MIN };
+
+    let add = a.saturating_add(b);
+    let sub = a.saturating_sub(b);
+    let mul = a.saturating_mul(b);
+    clog!(add, sub, mul);
+
+    if add_overflows_i128(a, b) {
+        cvlr_assert_eq!(add, bound(b > 0));
+    } else {
+        cvlr_assert_eq!(add, a.wrapping_add(b));
+    }
+    if sub_overflows_i128(a, b) {
+        cvlr_assert_eq!(sub, bound(b < 0));
+    } else {
+        cvlr_assert_eq!(sub, a.wrapping_sub(b));
+    }
+    if mul_overflows_i128(a, b) {
+        cvlr_assert_eq!(mul, bound((a < 0) == (b < 0)));
+    } else {
+        cvlr_assert_eq!(mul, a.wrapping_mul(b));
+    }
+}
+
+#[rule]
+pub fn rule_signed_fixed_rounding() {
+    let x: SFpNum = nondet();
+    clog!(x);
+
+    let floor = x.to_floor().unwrap();
+    let trunc = x.to_trunc().unwrap();
+    let ceil = x.to_ceil().unwrap();
+    clog!(floor, trunc, ceil);
+
+    cvlr_assert_le!(floor, trunc);
+    cvlr_assert_le!(trunc, ceil);
+    cvlr_assert_le!(ceil - floor, 1);
+    if x.is_negative() {
+        cvlr_assert_eq!(trunc, ceil);
+    } else {
+        cvlr_assert_eq!(trunc, floor);
+    }
+}
+
+#[rule]
+pub fn rule_signed_fixed_from_int() {
+    let v: i64 = nondet();
+    let x = SFpNum::from_i64(v);
+    clog!(v, x);
+
+    cvlr_assert_eq!(x.is_negative(), v < 0);
+    cvlr_assert_eq!(x.to_floor().unwrap(), v as i128);
+    cvlr_assert_eq!(x.to_trunc().unwrap(), v as i128);
+    cvlr_assert_eq!(x.to_ceil().unwrap(), v as i128);
+}
+
+#[rule]
+pub fn rule_signed_fixed_add_sub() {
+    let x: SFpNum = nondet();
+    let y: SFpNum = nondet();
+    clog!(x, y);
+
+    // -- addition and subtraction are exact
+    cvlr_assert!((x + y) - y == x);
+    cvlr_assert!(x + (-x) == SFpNum::zero());
+    cvlr_assert!(-(-x) == x);
+}
+
+#[rule]
+pub fn rule_signed_fixed_mul_div_sign() {
+    let x: SFpNum = nondet();
+    let y: SFpNum = nondet();
+    clog!(x, y);
+
+    let z = x * y;
+    clog!(z);
+    cvlr_assert!(z.abs() == x.abs() * y.abs());
+    if z != SFpNum::zero() {
+        cvlr_assert_eq!(z.is_negative(), x.is_negative() != y.is_negative());
+    }
+
+    cvlr_assume!(y != SFpNum::zero());
+    let w = x / y;
+    clog!(w);
+    cvlr_assert!(w.abs() == x.abs() / y.abs());
+    if w != SFpNum::zero() {
+        cvlr_assert_eq!(w.is_negative(), x.is_negative() != y.is_negative());
+    }
+}
@@ -0,0 +1,259 @@
+//! StableSwap invariant for a pool of two coins
+//!
+//! `D` and `y` are computed by Newton's method as in Curve pools, but every loop
+//! is explicitly bounded and the functions return `None` if the iteration does
+//! not converge. Rules prove convergence within the bound for the allowed range
+//! of amplification and balances.
+
+use crate::{
+    mul_div::{mul_div_ceil_u64, mul_div_floor_u128},
+    transfer_fee::MAX_FEE_BASIS_POINTS,
+};
+use cvlr::mathint::NativeInt as MathInt;
+use cvlr::prelude::*;
+
+const N_COINS: u128 = 2;
+/// `N_COINS^N_COINS`
+const ANN_FACTOR: u128 = 4;
+
+pub const MIN_AMP: u64 = 1;
+pub const MAX_AMP: u64 = 1_000_000;
+
+/// Largest balance for which all intermediate values fit into u128
+const MAX_BALANCE: u64 = 1 << 58;
+/// Largest ratio between the two balances of a pool
+const MAX_IMBALANCE: u64 = 100;
+
+/// Newton iterations for `D`, measured at most 8 in the allowed range
+const D_MAX_ITER: u32 = 10;
+/// Newton iterations for `y`, measured at most 17 in the allowed range
+const Y_MAX_ITER: u32 = 20;
+
+/// Invariant `D` of balances [x0] and [x1] with amplification [amp]
+///
+/// Solves `A n^n S + D = A n^n D + D^(n+1) / (n^n x0 x1)` starting from `D = S`.
+pub fn compute_d(x0: u64, x1: u64, amp: u64) -> Option<u128> {
+    let s = x0 as u128 + x1 as u128;
+    if s == 0 {
+        return Some(0);
+    }
+    let ann = (amp as u128) * ANN_FACTOR;
+    let mut d = s;
+    for _ in 0..D_MAX_ITER {
+        let d_p = mul_div_floor_u128(d, d, x0 as u128 * N_COINS)?;
+        let d_p = mul_div_floor_u128(d_p, d, x1 as u128 * N_COINS)?;
+        let prev = d;
+        let num = ann.checked_mul(s)?.checked_add(d_p.checked_mul(N_COINS)?)?;
+        let den = ann
+            .checked_sub(1)?
+            .checked_mul(d)?
+            .checked_add(d_p.checked_mul(N_COINS + 1)?)?;
+        d = mul_div_floor_u128(num, d, den)?;
+        if d.abs_diff(prev) <= 1 {
+            return Some(d);
+        }
+    }
+    None
+}
+
+/// Balance of the other coin that keeps the invariant at [d] when one coin has
+/// balance [x]
+///
+/// Solves `y^2 + (b - D) y = c` starting from `y = D`.
+pub fn get_y(x: u64, d: u128, amp: u64) -> Option<u128> {
+    let ann = (amp as u128) * ANN_FACTOR;
+    let c = mul_div_floor_u128(d, d, x as u128 * N_COINS)?;
+    let c = mul_div_floor_u128(c, d, ann * N_COINS)?;
+    let b = (x as u128).checked_add(d.checked_div(ann)?)?;
+    let mut y = d;
+    for _ in 0..Y_MAX_ITER {
+        let prev = y;
+        let num = y.checked_mul(y)?.checked_add(c)?;
+        let den = y.checked_mul(2)?.checked_add(b)?.checked_sub(d)?;
+        y = num.checked_div(den)?;
+        if y.abs_diff(prev) <= 1 {
+            return Some(y);
+        }
+    }
+    None
+}
+
+/// Amplification and balances for which the solvers are proven to converge
+fn is_in_range(x0: u64, x1: u64, amp: u64) -> bool {
+    let max_imbalance = MAX_IMBALANCE as u128;
+    (MIN_AMP..=MAX_AMP).contains(&amp)
+        && 0 < x0
+        && x0 <= MAX_BALANCE
+        && 0 < x1
+        && x1 <= MAX_BALANCE
+        && x0 as u128 <= x1 as u128 * max_imbalance
+        && x1 as u128 <= x0 as u128 * max_imbalance
+}
+
+/// StableSwap pool of tokens A and B
+struct StableSwapPool {
+    pub balance_a: u64,
+    pub balance_b: u64,
+    pub amp: u64,
+    /// Swap fee in basis points, retained by the pool
+    pub fee_bps: u16,
+}
+
+impl StableSwapPool {
+    pub fn is_in_range(&self) -> bool {
+        is_in_range(self.balance_a, self.balance_b, self.amp)
+    }
+
+    pub fn invariant(&self) -> Option<u128> {
+        compute_d(self.balance_a, self.balance_b, self.amp)
+    }
+
+    /// Amount of the output token for [amount_in] of the input token
+    ///
+    /// The output is reduced by one unit for the tolerance of each solver, and
+    /// the fee is rounded up, both in favour of the pool.
+    fn swap_out(&self, balance_in: u64, balance_out: u64, amount_in: u64) -> Option<u64> {
+        let d = self.invariant()?;
+        let balance_in_post = balance_in.checked_add(amount_in)?;
+        let balance_out_post = get_y(balance_in_post, d, self.amp)?;
+        let out: u64 = (balance_out as u128)
+            .checked_sub(balance_out_post)?
+            .checked_sub(2)?
+            .try_into()
+            .ok()?;
+        let fee = mul_div_ceil_u64(out, self.fee_bps as u64, MAX_FEE_BASIS_POINTS as u64)?;
+        Some(out - fee)
+    }
+
+    pub fn swap_a_for_b(&mut self, amount_in: u64) -> Option<u64> {
+        let out = self.swap_out(self.balance_a, self.balance_b, amount_in)?;
+        self.balance_a = self.balance_a.checked_add(amount_in)?;
+        self.balance_b = self.balance_b.checked_sub(out)?;
+        Some(out)
+    }
+
+    pub fn swap_b_for_a(&mut self, amount_in: u64) -> Option<u64> {
+        let out = self.swap_out(self.balance_b, self.balance_a, amount_in)?;
+        self.balance_b = self.balance_b.checked_add(amount_in)?;
+        self.balance_a = self.balance_a.checked_sub(out)?;
+        Some(out)
+    }
+}
+
+impl cvlr::nondet::Nondet for StableSwapPool {
+    fn nondet() -> Self {
+        let pool = Self {
+            balance_a: nondet(),
+            balance_b: nondet(),
+            amp: nondet(),
+            fee_bps: nondet(),
+        };
+        cvlr_assume!(pool.is_in_range());
+        cvlr_assume!(pool.fee_bps <= MAX_FEE_BASIS_POINTS);
+        pool
+    }
+}
+
+impl cvlr::log::CvlrLog for StableSwapPool {
+    #[inline(always)]
+    fn log(&self, tag: &str, logger: &mut cvlr::log::CvlrLogger) {
+        use cvlr::log::cvlr_log_with;
+        cvlr_log_with("", &tag, logger);
+        cvlr_log_with("\tbalance_a", &self.balance_a, logger);
+        cvlr_log_with("\tbalance_b", &self.balance_b, logger);
+        cvlr_log_with("\tamp", &self.amp, logger);
+        cvlr_log_with("\tfee_bps", &self.fee_bps, logger);
+    }
+}
+
+/// Balance [x] is far enough from zero for `get_y` to converge with invariant [d]
+fn assume_get_y_in_range(x: u64, d: u128) {
+    cvlr_assume!(x <= MAX_BALANCE);
+    cvlr_assume!(MathInt::from(x) * MAX_IMBALANCE * 2 >= MathInt::from(d));
+}
+
+#[rule]
+pub fn rule_stableswap_d_converges() {
+    let pool: StableSwapPool = nondet();
+    clog!(pool);
+
+    let d = pool.invariant();
+    clog!(d);
+    // -- converges within the iteration bound
+    cvlr_assert!(d.is_some());
+    // -- the invariant is at most the sum of the balances
+    let d = MathInt::from(d.unwrap());
+    cvlr_assert!(d > MathInt::from(0u64));
+    cvlr_assert_le!(
+        d,
+        MathInt::from(pool.balance_a) + MathInt::from(pool.balance_b)
+    );
+}
+
+#[rule]
+pub fn rule_stableswap_get_y_converges() {
+    let pool: StableSwapPool = nondet();
+    let d = pool.invariant().unwrap();
+    let x: u64 = nondet();
+    assume_get_y_in_range(x, d);
+    clog!(pool, d, x);
+
+    let y = get_y(x, d, pool.amp);
+    clog!(y);
+    // -- converges within the iteration bound
+    cvlr_assert!(y.is_some());
+}
+
+#[rule]
+pub fn rule_stableswap_get_y_monotone() {
+    let pool: StableSwapPool = nondet();
+    let d = pool.invariant().unwrap();
+    let x1: u64 = nondet();
+    let x2: u64 = nondet();
+    cvlr_assume!(x1 <= x2);
+    assume_get_y_in_range(x1, d);
+    assume_get_y_in_range(x2, d);
+    clog!(pool, d, x1, x2);
+
+    let y1 = get_y(x1, d, pool.amp).unwrap();
+    let y2 = get_y(x2, d, pool.amp).unwrap();
+    clog!(y1, y2);
+    // -- more of one coin requires less of the other
+    cvlr_assert_ge!(y1, y2);
+}
+
+#[rule]
+pub fn rule_stableswap_d_swap_a_for_b() {
+    let mut pool: StableSwapPool = nondet();
+    let d_pre = pool.invariant().unwrap();
+    clog!(pool, d_pre);
+
+    let amount_in: u64 = nondet();
+    let out = pool.swap_a_for_b(amount_in);
+    clog!(stringify!(pool.swap_a_for_b(amount_in)));
+    clog!(amount_in, out);
+    cvlr_assume!(out.is_some());
+    cvlr_assume!(pool.is_in_range());
+
+    let d_post = pool.invariant().unwrap();
+    clog!(pool, d_post);
+    cvlr_assert_ge!(d_post, d_pre);
+}
+
+#[rule]
+pub fn rule_stableswap_d_swap_b_for_a() {
+    let mut pool: StableSwapPool = nondet();
+    let d_pre = pool.invariant().unwrap();
+    clog!(pool, d_pre);
+
+    let amount_in: u64 = nondet();
+    let out = pool.swap_b_for_a(amount_in);
+    clog!(stringify!(pool.swap_b_for_a(amount_in)));
+    clog!(amount_in, out);
+    cvlr_assume!(out.is_some());
+    cvlr_assume!(pool.is_in_range());
+
+    let d_post = pool.invariant().unwrap();
+    clog!(pool, d_post);
+    cvlr_assert_ge!(d_post, d_pre);
+}
@@ -0,0 +1,333 @@
+use cvlr::mathint::NativeInt as MathInt;
+use cvlr_fixed::native_fixed::NativeFixedU128;
+
+/// Accumulated reward per staked token
+type RewardPerShare = NativeFixedU128<48>;
+
+/// Staking pool that distributes funded rewards pro rata to the stake
+///
+/// Uses the `acc_reward_per_share` accumulator. A staker is entitled to
+/// `amount * acc_reward_per_share - reward_debt`. The debt is rounded up when
+/// it is reset and the payout is rounded down, so rounding dust stays in the
+/// pool.
+struct StakingPool {
+    pub total_staked: u64,
+    pub acc_reward_per_share: RewardPerShare,
+    /// Funded rewards that have not been claimed yet
+    pub reward_balance: u64,
+}
+
+struct Staker {
+    pub amount: u64,
+    pub reward_debt: u64,
+}
+
+impl StakingPool {
+    pub fn pending(&self, staker: &Staker) -> u64 {
+        let accrued = (self.acc_reward_per_share * staker.amount).to_floor();
+        let debt = MathInt::from(staker.reward_debt);
+        if accrued > debt {
+            (accrued - debt).into()
+        } else {
+            0
+        }
+    }
+
+    fn reset_debt(&self, staker: &mut Staker) {
+        staker.reward_debt = (self.acc_reward_per_share * staker.amount).to_ceil().into();
+    }
+
+    /// Distribute [amount] to all stakers, rounded down
+    pub fn fund(&mut self, amount: u64) {
+        require_gt!(amount, 0);
+        require_gt!(self.total_staked, 0);
+        self.acc_reward_per_share =
+            self.acc_reward_per_share + RewardPerShare::from(amount) / self.total_staked;
+        self.reward_balance = self.reward_balance.checked_add(amount).unwrap();
+    }
+
+    pub fn claim(&mut self, staker: &mut Staker) -> u64 {
+        let reward = self.pending(staker);
+        self.reward_balance = self.reward_balance.checked_sub(reward).unwrap();
+        staker.reward_debt = staker.reward_debt.checked_add(reward).unwrap();
+        reward
+    }
+
+    /// Stake [amount] tokens, returns the reward claimed on the way
+    pub fn stake(&mut self, staker: &mut Staker, amount: u64) -> u64 {
+        require_gt!(amount, 0);
+        let reward = self.claim(staker);
+        staker.amount = staker.amount.checked_add(amount).unwrap();
+        self.total_staked = self.total_staked.checked_add(amount).unwrap();
+        self.reset_debt(staker);
+        reward
+    }
+
+    /// Unstake [amount] tokens, returns the reward claimed on the way
+    pub fn unstake(&mut self, staker: &mut Staker, amount: u64) -> u64 {
+        let reward = self.claim(staker);
+        staker.amount = staker.amount.checked_sub(amount).unwrap();
+        self.total_staked = self.total_staked.checked_sub(amount).unwrap();
+        self.reset_debt(staker);
+        reward
+    }
+}
+
+mod fv {
+    use super::*;
+    use cvlr::prelude::*;
+
+    /// `2^F` of [RewardPerShare]
+    fn base() -> MathInt {
+        MathInt::from(RewardPerShare::one().to_bits())
+    }
+
+    /// Pool with two stakers that hold the whole stake
+    struct FvStakingPool {
+        reward_balance: MathInt,
+        acc_reward_per_share: MathInt,
+        entitled_a: MathInt,
+        entitled_b: MathInt,
+        pending_a: MathInt,
+        pending_b: MathInt,
+    }
+
+    /// Exact reward [staker] is entitled to, scaled by `2^F`
+    fn entitled(pool: &StakingPool, staker: &Staker) -> MathInt {
+        let accrued = MathInt::from(pool.acc_reward_per_share.to_bits()) * staker.amount;
+        let debt = MathInt::from(staker.reward_debt) * base();
+        if accrued > debt {
+            accrued - debt
+        } else {
+            MathInt::from(0u64)
+        }
+    }
+
+    impl FvStakingPool {
+        pub fn new(pool: &StakingPool, a: &Staker, b: &Staker) -> Self {
+            Self {
+                reward_balance: pool.reward_balance.into(),
+                acc_reward_per_share: pool.acc_reward_per_share.to_bits().into(),
+                entitled_a: entitled(pool, a),
+                entitled_b: entitled(pool, b),
+                pending_a: pool.pending(a).into(),
+                pending_b: pool.pending(b).into(),
+            }
+        }
+
+        /// Funded rewards cover everything the stakers are entitled to
+        pub fn assume_solvency(&self) {
+            let p = self;
+            cvlr_assume!(p.entitled_a + p.entitled_b <= p.reward_balance * base());
+        }
+
+        pub fn check_solvency(&self) {
+            let p = self;
+            cvlr_assert_le!(p.entitled_a + p.entitled_b, p.reward_balance * base());
+            // -- hence everything claimable is funded
+            cvlr_assert_le!(p.pending_a + p.pending_b, p.reward_balance);
+        }
+    }
+
+    impl cvlr::nondet::Nondet for StakingPool {
+        fn nondet() -> Self {
+            Self {
+                total_staked: nondet(),
+                acc_reward_per_share: nondet(),
+                reward_balance: nondet(),
+            }
+        }
+    }
+
+    impl cvlr::nondet::Nondet for Staker {
+        fn nondet() -> Self {
+            Self {
+                amount: nondet(),
+                reward_debt: nondet(),
+            }
+        }
+    }
+
+    impl cvlr::log::CvlrLog for FvStakingPool {
+        #[inline(always)]
+        fn log(&self, tag: &str, logger: &mut cvlr::log::CvlrLogger) {
+            use cvlr::log::cvlr_log_with;
+            cvlr_log_with("", &tag, logger);
+            cvlr_log_with("\treward_balance", &self.reward_balance, logger);
+            cvlr_log_with("\tacc_reward_per_share", &self.acc_reward_per_share, logger);
+            cvlr_log_with("\tentitled_a", &self.entitled_a, logger);
+            cvlr_log_with("\tentitled_b", &self.entitled_b, logger);
+            cvlr_log_with("\tpending_a", &self.pending_a, logger);
+            cvlr_log_with("\tpending_b", &self.pending_b, logger);
+        }
+    }
+
+    /// Pool and two stakers that hold the whole stake
+    fn nondet_pool() -> (StakingPool, Staker, Staker) {
+        let pool: StakingPool = nondet();
+        let a: Staker = nondet();
+        let b: Staker = nondet();
+        cvlr_assume!(
+            MathInt::from(pool.total_staked) == MathInt::from(a.amount) + MathInt::from(b.amount)
+        );
+        (pool, a, b)
+    }
+
+    #[rule]
+    pub fn rule_staking_solvency_fund() {
+        let (mut pool, a, b) = nondet_pool();
+
+        let fv_pool_pre = FvStakingPool::new(&pool, &a, &b);
+        fv_pool_pre.assume_solvency();
+
+        let amount: u64 = nondet();
+        pool.fund(amount);
+        clog!(stringify!(pool.fund(amount);));
+
+        let fv_pool_post = FvStakingPool::new(&pool, &a, &b);
+
+        clog!(fv_pool_pre, amount, fv_pool_post);
+
+        fv_pool_post.check_solvency();
+    }
+
+    #[rule]
+    pub fn rule_staking_solvency_stake() {
+        let (mut pool, mut a, b) = nondet_pool();
+
+        let fv_pool_pre = FvStakingPool::new(&pool, &a, &b);
+        fv_pool_pre.assume_solvency();
+
+        let amount: u64 = nondet();
+        let _ = pool.stake(&mut a, amount);
+        clog!(stringify!(pool.stake(&mut a, amount);));
+
+        let fv_pool_post = FvStakingPool::new(&pool, &a, &b);
+
+        clog!(fv_pool_pre, amount, fv_pool_post);
+
+        fv_pool_post.check_solvency();
+    }
+
+    #[rule]
+    pub fn rule_staking_solvency_unstake() {
+        let (mut pool, mut a, b) = nondet_pool();
+
+        let fv_pool_pre = FvStakingPool::new(&pool, &a, &b);
+        fv_pool_pre.assume_solvency();
+
+        let amount: u64 = nondet();
+        let _ = pool.unstake(&mut a, amount);
+        clog!(stringify!(pool.unstake(&mut a, amount);));
+
+        let fv_pool_post = FvStakingPool::new(&pool, &a, &b);
+
+        clog!(fv_pool_pre, amount, fv_pool_post);
+
+        fv_pool_post.check_solvency();
+    }
+
+    #[rule]
+    pub fn rule_staking_solvency_claim() {
+        let (mut pool, mut a, b) = nondet_pool();
+
+        let fv_pool_pre = FvStakingPool::new(&pool, &a, &b);
+        fv_pool_pre.assume_solvency();
+
+        let reward = pool.claim(&mut a);
+        clog!(stringify!(pool.claim(&mut a);));
+
+        let fv_pool_post = FvStakingPool::new(&pool, &a, &b);
+
+        clog!(fv_pool_pre, reward, fv_pool_post);
+
+        fv_pool_post.check_solvency();
+    }
+
+    /// Funding distributes at most the funded amount
+    #[rule]
+    pub fn rule_staking_fund_dust_stays() {
+        let mut pool: StakingPool = nondet();
+
+        let acc_pre = MathInt::from(pool.acc_reward_per_share.to_bits());
+
+        let amount: u64 = nondet();
+        pool.fund(amount);
+        clog!(stringify!(pool.fund(amount)));
+
+        let acc_post = MathInt::from(pool.acc_reward_per_share.to_bits());
+
+        clog!(acc_pre, amount, acc_post);
+
+        cvlr_assert_le!(
+            (acc_post - acc_pre) * pool.total_staked,
+            MathInt::from(amount) * base()
+        );
+    }
+
+    /// A claim pays at most the exact entitlement
+    #[rule]
+    pub fn rule_staking_claim_dust_stays() {
+        let mut pool: StakingPool = nondet();
+        let mut staker: Staker = nondet();
+
+        let entitled_pre = entitled(&pool, &staker);
+
+        let reward = pool.claim(&mut staker);
+        clog!(stringify!(pool.claim(&mut staker)));
+
+        clog!(entitled_pre, reward);
+
+        cvlr_assert_le!(MathInt::from(reward) * base(), entitled_pre);
+    }
+
+    #[rule]
+    pub fn rule_staking_no_interference_stake() {
+        let (mut pool, a, mut b) = nondet_pool();
+
+        let pending_pre = pool.pending(&a);
+
+        let amount: u64 = nondet();
+        let _ = pool.stake(&mut b, amount);
+        clog!(stringify!(pool.stake(&mut b, amount)));
+
+        let pending_post = pool.pending(&a);
+
+        clog!(pending_pre, amount, pending_post);
+
+        cvlr_assert_eq!(pending_pre, pending_post);
+    }
+
+    #[rule]
+    pub fn rule_staking_no_interference_unstake() {
+        let (mut pool, a, mut b) = nondet_pool();
+
+        let pending_pre = pool.pending(&a);
+
+        let amount: u64 = nondet();
+        let _ = pool.unstake(&mut b, amount);
+        clog!(stringify!(pool.unstake(&mut b, amount)));
+
+        let pending_post = pool.pending(&a);
+
+        clog!(pending_pre, amount, pending_post);
+
+        cvlr_assert_eq!(pending_pre, pending_post);
+    }
+
+    #[rule]
+    pub fn rule_staking_no_interference_claim() {
+        let (mut pool, a, mut b) = nondet_pool();
+
+        let pending_pre = pool.pending(&a);
+
+        let reward = pool.claim(&mut b);
+        clog!(stringify!(pool.claim(&mut b)));
+
+        let pending_post = pool.pending(&a);
+
+        clog!(pending_pre, reward, pending_post);
+
+        cvlr_assert_eq!(pending_pre, pending_post);
+    }
+}
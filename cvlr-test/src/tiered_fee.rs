@@ -0,0 +1,249 @@
+//! Tiered (volume-based) fee schedules
+//!
+//! A schedule is an ordered list of tiers. Each tier starts at a threshold
+//! and charges its own rate in basis points. In [TierMode::Marginal] mode
+//! every tier charges only the portion of the amount that falls inside it,
+//! like income tax brackets. In [TierMode::WholeAmount] mode the rate of the
+//! highest tier reached is charged on the whole amount, which makes the fee
+//! jump at tier boundaries.
+
+use crate::transfer_fee::MAX_FEE_BASIS_POINTS;
+use cvlr::mathint::NativeInt as MathInt;
+use cvlr::prelude::*;
+use std::cmp;
+
+const ONE_IN_BASIS_POINTS: u128 = MAX_FEE_BASIS_POINTS as u128;
+
+/// Number of tiers used by the rules
+const NUM_TIERS: usize = 3;
+
+/// How the rate of a tier is applied to an amount
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum TierMode {
+    /// Each rate applies only to the portion of the amount inside its tier
+    Marginal,
+    /// The rate of the highest tier reached applies to the whole amount
+    WholeAmount,
+}
+
+/// A single tier of a fee schedule
+#[derive(Clone, Copy)]
+pub struct FeeTier {
+    /// Smallest amount to which this tier applies
+    pub threshold: u64,
+    /// Fee rate of the tier in basis points
+    pub fee_basis_points: u16,
+}
+
+/// Fee schedule with `N` tiers ordered by threshold
+pub struct TieredFee<const N: usize> {
+    pub tiers: [FeeTier; N],
+    pub mode: TierMode,
+}
+
+impl<const N: usize> TieredFee<N> {
+    /// A schedule is valid if the first tier starts at 0, thresholds are
+    /// strictly increasing, and no rate exceeds 100%
+    pub fn is_valid(&self) -> bool {
+        match self.tiers.first() {
+            Some(first) if first.threshold == 0 => {}
+            _ => return false,
+        }
+        self.tiers
+            .iter()
+            .all(|t| t.fee_basis_points <= MAX_FEE_BASIS_POINTS)
+            && self
+                .tiers
+                .windows(2)
+                .all(|w| w[0].threshold < w[1].threshold)
+    }
+
+    /// True if rates do not decrease from one tier to the next
+    pub fn has_non_decreasing_rates(&self) -> bool {
+        self.tiers
+            .windows(2)
+            .all(|w| w[0].fee_basis_points <= w[1].fee_basis_points)
+    }
+
+    /// Highest rate of the schedule
+    pub fn max_basis_points(&self) -> u16 {
+        self.tiers
+            .iter()
+            .fold(0, |acc, t| cmp::max(acc, t.fee_basis_points))
+    }
+
+    /// Index of the highest tier whose threshold is reached by [amount]
+    pub fn tier_of(&self, amount: u64) -> usize {
+        let mut idx = 0;
+        for (i, t) in self.tiers.iter().enumerate() {
+            if t.threshold <= amount {
+                idx = i;
+            }
+        }
+        idx
+    }
+
+    /// Fee multiplied by [ONE_IN_BASIS_POINTS], before rounding
+    fn fee_numerator(&self, amount: u64) -> Option<u128> {
+        match self.mode {
+            TierMode::WholeAmount => {
+                let bps = self.tiers[self.tier_of(amount)].fee_basis_points as u128;
+                (amount as u128).checked_mul(bps)
+            }
+            TierMode::Marginal => {
+                let mut numerator: u128 = 0;
+                for (i, t) in self.tiers.iter().enumerate() {
+                    if amount <= t.threshold {
+                        break;
+                    }
+                    let hi = match self.tiers.get(i + 1) {
+                        Some(next) => cmp::min(amount, next.threshold),
+                        None => amount,
+                    };
+                    let portion = (hi - t.threshold) as u128;
+                    numerator =
+                        numerator.checked_add(portion.checked_mul(t.fee_basis_points as u128)?)?;
+                }
+                Some(numerator)
+            }
+        }
+    }
+
+    /// Calculate the fee for [amount]
+    ///
+    /// The fee is rounded up once, after all tiers are accumulated, so that
+    /// splitting an amount across tiers does not lose precision.
+    pub fn calculate_fee(&self, amount: u64) -> Option<u64> {
+        self.fee_numerator(amount)?
+            .div_ceil(ONE_IN_BASIS_POINTS)
+            .try_into()
+            .ok()
+    }
+}
+
+impl cvlr::nondet::Nondet for FeeTier {
+    fn nondet() -> Self {
+        Self {
+            threshold: nondet(),
+            fee_basis_points: nondet(),
+        }
+    }
+}
+
+impl cvlr::log::CvlrLog for TieredFee<NUM_TIERS> {
+    fn log(&self, tag: &str, logger: &mut cvlr::log::CvlrLogger) {
+        use cvlr::log::cvlr_log_with;
+        cvlr_log_with("", &tag, logger);
+        cvlr_log_with("\tmarginal", &(self.mode == TierMode::Marginal), logger);
+        for t in self.tiers.iter() {
+            cvlr_log_with("\tthreshold", &t.threshold, logger);
+            cvlr_log_with("\tfee_basis_points", &t.fee_basis_points, logger);
+        }
+    }
+}
+
+/// Nondet valid schedule with the given [mode]
+fn nondet_tiered_fee(mode: TierMode) -> TieredFee<NUM_TIERS> {
+    let tf = TieredFee {
+        tiers: [nondet(), nondet(), nondet()],
+        mode,
+    };
+    cvlr_assume!(tf.is_valid());
+    tf
+}
+
+fn check_monotonicity(tf: &TieredFee<NUM_TIERS>) {
+    let amount_x: u64 = nondet();
+    let amount_y: u64 = nondet();
+    cvlr_assume!(amount_x > amount_y);
+    clog!(amount_x, amount_y);
+
+    let fee_x = tf.calculate_fee(amount_x).unwrap();
+    let fee_y = tf.calculate_fee(amount_y).unwrap();
+    cvlr_assert_ge!(fee_x, fee_y);
+}
+
+#[rule]
+pub fn rule_tiered_fee_monotonicity_marginal() {
+    let tf = nondet_tiered_fee(TierMode::Marginal);
+    clog!(tf);
+    check_monotonicity(&tf);
+}
+
+/// Whole-amount fees are only monotone when rates do not decrease. A volume
+/// discount (decreasing rates) lets a larger amount pay a smaller fee.
+#[rule]
+pub fn rule_tiered_fee_monotonicity_whole_amount() {
+    let tf = nondet_tiered_fee(TierMode::WholeAmount);
+    cvlr_assume!(tf.has_non_decreasing_rates());
+    clog!(tf);
+    check_monotonicity(&tf);
+}
+
+/// Marginal fees are continuous: one more token costs at most one more token
+/// of fee, no matter which boundary is crossed
+#[rule]
+pub fn rule_tiered_fee_continuity_marginal() {
+    let tf = nondet_tiered_fee(TierMode::Marginal);
+    clog!(tf);
+
+    let amount: u64 = nondet();
+    cvlr_assume!(amount < u64::MAX);
+    clog!(amount);
+
+    let fee = tf.calculate_fee(amount).unwrap();
+    let fee_next = tf.calculate_fee(amount + 1).unwrap();
+    cvlr_assert_ge!(fee_next, fee);
+    cvlr_assert_le!(MathInt::from(fee_next), MathInt::from(fee) + 1);
+}
+
+/// Whole-amount fees are continuous inside a tier and jump at its threshold
+/// by at most the rate increase applied to the threshold, plus rounding
+#[rule]
+pub fn rule_tiered_fee_jump_whole_amount() {
+    let tf = nondet_tiered_fee(TierMode::WholeAmount);
+    cvlr_assume!(tf.has_non_decreasing_rates());
+    clog!(tf);
+
+    let amount: u64 = nondet();
+    cvlr_assume!(amount > 0);
+    clog!(amount);
+
+    let fee_prev = tf.calculate_fee(amount - 1).unwrap();
+    let fee = tf.calculate_fee(amount).unwrap();
+
+    let tier_prev = tf.tier_of(amount - 1);
+    let tier = tf.tier_of(amount);
+    clog!(tier_prev, tier);
+
+    if tier_prev == tier {
+        cvlr_assert_le!(MathInt::from(fee), MathInt::from(fee_prev) + 1);
+    } else {
+        let rate_increase = tf.tiers[tier].fee_basis_points - tf.tiers[tier_prev].fee_basis_points;
+        let max_jump = (MathInt::from(amount) * rate_increase as u64)
+            .div_ceil(MathInt::from(MAX_FEE_BASIS_POINTS));
+        cvlr_assert_le!(MathInt::from(fee), MathInt::from(fee_prev) + max_jump + 1);
+    }
+}
+
+/// The effective rate never exceeds the rate of the most expensive tier
+#[rule]
+pub fn rule_tiered_fee_bounded_by_top_tier() {
+    let mode = if nondet::<bool>() {
+        TierMode::Marginal
+    } else {
+        TierMode::WholeAmount
+    };
+    let tf = nondet_tiered_fee(mode);
+    clog!(tf);
+
+    let amount: u64 = nondet();
+    clog!(amount);
+
+    let fee = tf.calculate_fee(amount).unwrap();
+    clog!(fee);
+    let max_fee = (MathInt::from(amount) * tf.max_basis_points() as u64)
+        .div_ceil(MathInt::from(MAX_FEE_BASIS_POINTS));
+    cvlr_assert_le!(MathInt::from(fee), max_fee);
+    cvlr_assert_le!(fee, amount);
+}
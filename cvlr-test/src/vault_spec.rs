@@ -1,42 +1,16 @@
+use crate::mul_div::mul_div_floor_u64;
+
 struct VaultState {
     pub shares_total: u64,
     pub token_total: u64,
 }
 
-pub fn mul_div_floor(a: u64, b: u64, c: u64) -> u64 {
-    (a as u128)
-        .checked_mul(b as u128)
-        .unwrap()
-        .checked_div(c as u128)
-        .unwrap()
-        .try_into()
-        .unwrap()
-}
-
-pub fn mul_div_ceil(a: u64, b: u64, c: u64) -> u64 {
-    (a as u128)
-        .checked_mul(b as u128)
-        .unwrap()
-        .div_ceil(c as u128)
-        .try_into()
-        .unwrap()
-}
-
-macro_rules! require_gt {
-    ($lhs: expr, $rhs: expr) => {
-        if $lhs > $rhs {
-        } else {
-            panic!()
-        }
-    };
-}
-
 impl VaultState {
     pub fn deposit(&mut self, tkn: u64) -> u64 {
         let shares_for_user = if self.shares_total == self.token_total {
             tkn
         } else {
-            mul_div_floor(tkn, self.shares_total, self.token_total)
+            mul_div_floor_u64(tkn, self.shares_total, self.token_total).unwrap()
         };
 
         self.mint_shares(shares_for_user);
@@ -49,7 +23,7 @@ impl VaultState {
         let tkn_for_user = if self.shares_total == self.token_total {
             shares
         } else {
-            mul_div_floor(shares, self.token_total, self.shares_total)
+            mul_div_floor_u64(shares, self.token_total, self.shares_total).unwrap()
         };
 
         self.burn_shares(shares);
@@ -0,0 +1,234 @@
+use crate::mul_div::mul_div_floor_u64;
+
+/// Linear vesting of [total] tokens with a cliff
+///
+/// Nothing vests before the cliff. From the cliff on, the vested amount grows
+/// linearly from `start` and reaches `total` at `start + duration`.
+struct VestingSchedule {
+    pub start: u64,
+    pub cliff: u64,
+    pub duration: u64,
+    pub total: u64,
+    /// Tokens already released to the beneficiary
+    pub released: u64,
+}
+
+impl VestingSchedule {
+    pub fn is_valid(&self) -> bool {
+        match self.start.checked_add(self.duration) {
+            Some(end) => self.start <= self.cliff && self.cliff <= end,
+            None => false,
+        }
+    }
+
+    /// Tokens vested at [now], rounded down
+    pub fn vested_amount(&self, now: u64) -> u64 {
+        if now < self.cliff {
+            return 0;
+        }
+        let elapsed = now - self.start;
+        if elapsed >= self.duration {
+            self.total
+        } else {
+            mul_div_floor_u64(self.total, elapsed, self.duration).unwrap()
+        }
+    }
+
+    pub fn releasable_amount(&self, now: u64) -> u64 {
+        self.vested_amount(now).saturating_sub(self.released)
+    }
+
+    /// Release everything vested at [now], returns the released amount
+    pub fn release(&mut self, now: u64) -> u64 {
+        let amount = self.releasable_amount(now);
+        self.released = self.released.checked_add(amount).unwrap();
+        amount
+    }
+}
+
+mod fv {
+    use super::*;
+    use cvlr::mathint::NativeInt as MathInt;
+    use cvlr::prelude::*;
+    use cvlr_sysvars::{cvlr_nondet_clock, cvlr_nondet_clock_after, cvlr_nondet_epoch_schedule};
+
+    struct FvVestingSchedule {
+        total: MathInt,
+        released: MathInt,
+    }
+
+    impl FvVestingSchedule {
+        pub fn new(schedule: &VestingSchedule) -> Self {
+            Self {
+                total: schedule.total.into(),
+                released: schedule.released.into(),
+            }
+        }
+
+        pub fn assume_released_le_total(&self) {
+            let s = self;
+            cvlr_assume!(s.released <= s.total);
+        }
+
+        pub fn check_released_le_total(&self) {
+            let s = self;
+            cvlr_assert_le!(s.released, s.total);
+        }
+    }
+
+    impl From<&VestingSchedule> for FvVestingSchedule {
+        fn from(schedule: &VestingSchedule) -> Self {
+            Self::new(schedule)
+        }
+    }
+
+    impl cvlr::nondet::Nondet for VestingSchedule {
+        fn nondet() -> Self {
+            let schedule = Self {
+                start: nondet(),
+                cliff: nondet(),
+                duration: nondet(),
+                total: nondet(),
+                released: nondet(),
+            };
+            cvlr_assume!(schedule.is_valid());
+            schedule
+        }
+    }
+
+    impl cvlr::log::CvlrLog for FvVestingSchedule {
+        #[inline(always)]
+        fn log(&self, tag: &str, logger: &mut cvlr::log::CvlrLogger) {
+            use cvlr::log::cvlr_log_with;
+            cvlr_log_with("", &tag, logger);
+            cvlr_log_with("\ttotal", &self.total, logger);
+            cvlr_log_with("\treleased", &self.released, logger);
+        }
+    }
+
+    #[rule]
+    pub fn rule_vesting_monotone() {
+        let schedule: VestingSchedule = nondet();
+        let t1: u64 = nondet();
+        let t2: u64 = nondet();
+        cvlr_assume!(t1 <= t2);
+
+        let v1 = schedule.vested_amount(t1);
+        let v2 = schedule.vested_amount(t2);
+        clog!(t1, t2, v1, v2);
+
+        cvlr_assert_le!(v1, v2);
+    }
+
+    #[rule]
+    pub fn rule_vesting_zero_before_cliff() {
+        let schedule: VestingSchedule = nondet();
+        let now: u64 = nondet();
+        cvlr_assume!(now < schedule.cliff);
+
+        let vested = schedule.vested_amount(now);
+        clog!(now, vested);
+
+        cvlr_assert_eq!(vested, 0);
+    }
+
+    #[rule]
+    pub fn rule_vesting_total_after_end() {
+        let schedule: VestingSchedule = nondet();
+        let now: u64 = nondet();
+        cvlr_assume!(MathInt::from(now) >= MathInt::from(schedule.start) + schedule.duration);
+
+        let vested = schedule.vested_amount(now);
+        clog!(now, vested);
+
+        cvlr_assert_eq!(vested, schedule.total);
+    }
+
+    #[rule]
+    pub fn rule_vesting_vested_le_total() {
+        let schedule: VestingSchedule = nondet();
+        let now: u64 = nondet();
+
+        let vested = schedule.vested_amount(now);
+        clog!(now, vested);
+
+        cvlr_assert_le!(vested, schedule.total);
+    }
+
+    #[rule]
+    pub fn rule_vesting_released_le_vested() {
+        let mut schedule: VestingSchedule = nondet();
+        let now: u64 = nondet();
+        cvlr_assume!(schedule.released <= schedule.vested_amount(now));
+
+        let fv_schedule_pre: FvVestingSchedule = (&schedule).into();
+
+        let amount = schedule.release(now);
+        clog!(stringify!(schedule.release(now)));
+        clog!(amount);
+
+        let fv_schedule_post: FvVestingSchedule = (&schedule).into();
+
+        clog!(fv_schedule_pre, now, fv_schedule_post);
+
+        fv_schedule_post.check_released_le_total();
+        cvlr_assert_le!(schedule.released, schedule.vested_amount(now));
+
+        // -- and it stays so as time goes on
+        let later: u64 = nondet();
+        cvlr_assume!(now <= later);
+        cvlr_assert_le!(schedule.released, schedule.vested_amount(later));
+    }
+
+    /// Releasing at arbitrary times never pays out more than the total
+    #[rule]
+    pub fn rule_vesting_repeated_release() {
+        let mut schedule: VestingSchedule = nondet();
+
+        let fv_schedule_pre: FvVestingSchedule = (&schedule).into();
+        fv_schedule_pre.assume_released_le_total();
+
+        let t1: u64 = nondet();
+        let t2: u64 = nondet();
+        let t3: u64 = nondet();
+        let a1 = schedule.release(t1);
+        let a2 = schedule.release(t2);
+        let a3 = schedule.release(t3);
+        clog!(t1, a1, t2, a2, t3, a3);
+
+        let fv_schedule_post: FvVestingSchedule = (&schedule).into();
+
+        clog!(fv_schedule_pre, fv_schedule_post);
+
+        fv_schedule_post.check_released_le_total();
+        cvlr_assert_le!(
+            fv_schedule_pre.released + a1 + a2 + a3,
+            fv_schedule_pre.total
+        );
+    }
+
+    /// Releasing at the time of successive clocks
+    #[rule]
+    pub fn rule_vesting_release_with_clock() {
+        let mut schedule: VestingSchedule = nondet();
+        let epoch_schedule = cvlr_nondet_epoch_schedule();
+        let c1 = cvlr_nondet_clock(&epoch_schedule);
+        let c2 = cvlr_nondet_clock_after(&c1, &epoch_schedule);
+        // -- timestamps of a consistent clock are not negative
+        let (t1, t2) = (c1.unix_timestamp as u64, c2.unix_timestamp as u64);
+
+        let fv_schedule_pre: FvVestingSchedule = (&schedule).into();
+        fv_schedule_pre.assume_released_le_total();
+
+        let a1 = schedule.release(t1);
+        let a2 = schedule.release(t2);
+        clog!(t1, a1, t2, a2);
+
+        let fv_schedule_post: FvVestingSchedule = (&schedule).into();
+
+        clog!(fv_schedule_pre, fv_schedule_post);
+
+        fv_schedule_post.check_released_le_total();
+        cvlr_assert_le!(schedule.vested_amount(t1), schedule.vested_amount(t2));
+    }
+}